@@ -0,0 +1,349 @@
+use std::{
+    collections::BTreeSet,
+    fs::{self, File},
+    io::Write,
+    path::{Component, Path, PathBuf},
+};
+
+use anyhow::{bail, Context};
+use clap::ValueEnum;
+use tar::{Archive, Builder, EntryType, HeaderMode};
+
+use crate::{package_manifest::TargetFile, shell::Shell};
+
+/// The directories that archives can install files into.
+///
+/// Each one is located in the armory home.
+pub const LAYOUT: [&str; 4] = ["bin", "share", "completions", "etc"];
+
+/// Checks that a path in an archive names a file inside one of the layout
+/// directories.
+///
+/// Completion scripts must be placed in a directory named after their shell,
+/// as in `completions/bash/{name}`.
+pub fn check_path(path: &Path) -> anyhow::Result<()> {
+    let mut components = path.components();
+
+    match components.next() {
+        Some(Component::Normal(first)) if LAYOUT.iter().any(|dir| first == *dir) => {}
+        _ => bail!("{} is not inside one of {LAYOUT:?}", path.display()),
+    }
+
+    let rest: Vec<_> = components.collect();
+    if rest.is_empty() {
+        bail!("{} does not name a file", path.display());
+    }
+
+    if !rest
+        .iter()
+        .all(|component| matches!(component, Component::Normal(_)))
+    {
+        bail!("{} must not contain special components", path.display());
+    }
+
+    if path.starts_with("completions") && completions_shell(path).is_none() {
+        bail!("{} is not a file in completions/{{shell}}", path.display());
+    }
+
+    Ok(())
+}
+
+/// Returns the shell of a completion script at `completions/{shell}/{file}`.
+fn completions_shell(path: &Path) -> Option<Shell> {
+    let mut components = path.components().skip(1);
+    let shell = components.next()?.as_os_str().to_str()?;
+    let _file = components.next()?;
+    if components.next().is_some() {
+        return None;
+    }
+    Shell::from_str(shell, false).ok()
+}
+
+/// Returns the path that a file in an archive is installed to, relative to the
+/// armory home.
+///
+/// Completion scripts are installed to the directory that `armory env` sets up
+/// their shell to load completions from. Every other file is installed to its
+/// path in the archive. The path should be checked with [`check_path`] first.
+pub fn install_path(path: &Path) -> PathBuf {
+    match completions_shell(path) {
+        Some(shell) => shell
+            .completions_dir()
+            .join(path.file_name().expect("path should name a file")),
+        None => path.to_owned(),
+    }
+}
+
+/// Packs files into a tar archive.
+///
+/// Only deterministic metadata is recorded, so packing the same files always
+/// produces the same archive.
+pub fn pack(files: &[TargetFile], writer: impl Write) -> anyhow::Result<()> {
+    let mut builder = Builder::new(writer);
+    builder.mode(HeaderMode::Deterministic);
+
+    let mut dests = BTreeSet::new();
+    for file in files {
+        check_path(&file.dest)?;
+        if !dests.insert(install_path(&file.dest)) {
+            bail!("{} is included more than once", file.dest.display());
+        }
+        if !file.source.is_file() {
+            bail!("file does not exist at {}", file.source.display());
+        }
+        builder
+            .append_path_with_name(&file.source, &file.dest)
+            .with_context(|| format!("failed to add {} to archive", file.source.display()))?;
+    }
+
+    builder
+        .into_inner()
+        .and_then(|mut writer| writer.flush())
+        .context("failed to write archive")
+}
+
+/// Lists the files in a tar archive by the path they are installed to.
+///
+/// Returns an error if the archive contains anything other than files and
+/// directories, or files outside the layout directories.
+pub fn files(archive: &Path) -> anyhow::Result<Vec<PathBuf>> {
+    let file = File::open(archive).context("failed to open archive")?;
+    let mut archive = Archive::new(file);
+
+    let mut files = Vec::new();
+    for entry in archive.entries().context("failed to read archive")? {
+        let entry = entry.context("failed to read archive entry")?;
+        let path = entry.path().context("archive entry path is malformed")?;
+        match entry.header().entry_type() {
+            EntryType::Regular => {
+                check_path(&path)?;
+                files.push(install_path(&path));
+            }
+            EntryType::Directory => {}
+            other => bail!("{} has unsupported type {other:?}", path.display()),
+        }
+    }
+
+    Ok(files)
+}
+
+/// Unpacks the files in a tar archive into a directory.
+///
+/// The archive should be checked with [`files`] first. Each file is unpacked to
+/// its [`install_path`]. Existing files are replaced, and files in `bin/` are
+/// made executable.
+pub fn unpack(archive: &Path, root: &Path) -> anyhow::Result<()> {
+    let file = File::open(archive).context("failed to open archive")?;
+    let mut archive = Archive::new(file);
+
+    for entry in archive.entries().context("failed to read archive")? {
+        let mut entry = entry.context("failed to read archive entry")?;
+        if entry.header().entry_type() != EntryType::Regular {
+            continue;
+        }
+
+        let path = entry
+            .path()
+            .context("archive entry path is malformed")?
+            .into_owned();
+        check_path(&path)?;
+
+        let dest = root.join(install_path(&path));
+        if let Some(parent) = dest.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("failed to create {}", parent.display()))?;
+        }
+
+        crate::remove_existing(&dest)?;
+
+        entry
+            .unpack(&dest)
+            .with_context(|| format!("failed to unpack {}", dest.display()))?;
+
+        #[cfg(unix)]
+        if path.starts_with("bin") {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&dest, fs::Permissions::from_mode(0o700))
+                .context("failed to set binary permissions")?;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, path::Path};
+
+    use tempfile::NamedTempFile;
+
+    use super::{check_path, files, pack, unpack};
+    use crate::{package_manifest::TargetFile, shell::Shell};
+
+    #[test]
+    fn allowed_paths() {
+        for valid in [
+            "bin/rg",
+            "bin/rg.exe",
+            "share/man/man1/rg.1",
+            "completions/bash/rg",
+            "completions/zsh/_rg",
+            "completions/fish/rg.fish",
+            "completions/powershell/rg.ps1",
+            "etc/rg/config.toml",
+            "bin/./rg",
+        ] {
+            assert!(
+                check_path(Path::new(valid)).is_ok(),
+                "{valid} should be allowed"
+            );
+        }
+    }
+
+    #[test]
+    fn parent_components() {
+        for invalid in [
+            "..",
+            "../bin/rg",
+            "bin/../rg",
+            "bin/../../.bashrc",
+            "share/..",
+        ] {
+            assert!(
+                check_path(Path::new(invalid)).is_err(),
+                "{invalid} should be rejected"
+            );
+        }
+    }
+
+    #[test]
+    fn absolute_paths() {
+        for invalid in ["/bin/rg", "/etc/passwd", "/"] {
+            assert!(
+                check_path(Path::new(invalid)).is_err(),
+                "{invalid} should be rejected"
+            );
+        }
+    }
+
+    #[test]
+    fn outside_layout() {
+        for invalid in [
+            "",
+            "rg",
+            "lib/librg.so",
+            "cache/rg",
+            "./bin/rg",
+            "binaries/rg",
+        ] {
+            assert!(
+                check_path(Path::new(invalid)).is_err(),
+                "{invalid} should be rejected"
+            );
+        }
+    }
+
+    #[test]
+    fn completions_outside_shell_directory() {
+        for invalid in [
+            "completions/rg.bash",
+            "completions/bash",
+            "completions/tcsh/rg",
+            "completions/Bash/rg",
+            "completions/bash/nested/rg",
+        ] {
+            assert!(
+                check_path(Path::new(invalid)).is_err(),
+                "{invalid} should be rejected"
+            );
+        }
+    }
+
+    #[test]
+    fn layout_directories() {
+        for invalid in ["bin", "share", "completions", "etc", "bin/"] {
+            assert!(
+                check_path(Path::new(invalid)).is_err(),
+                "{invalid} should be rejected"
+            );
+        }
+    }
+
+    #[test]
+    fn completions_round_trip() {
+        let sources = tempfile::tempdir().unwrap();
+        let files_to_pack: Vec<_> = [
+            ("rg", "bin/rg"),
+            ("rg.bash", "completions/bash/rg"),
+            ("_rg", "completions/zsh/_rg"),
+            ("rg.1", "share/man/man1/rg.1"),
+        ]
+        .into_iter()
+        .map(|(source, dest)| {
+            let source = sources.path().join(source);
+            fs::write(&source, dest).unwrap();
+            TargetFile {
+                source,
+                dest: dest.into(),
+            }
+        })
+        .collect();
+
+        let mut archive = NamedTempFile::new().unwrap();
+        pack(&files_to_pack, archive.as_file_mut()).unwrap();
+
+        // completion scripts are installed where `armory env` loads them from
+        let bash = Shell::Bash.completions_dir().join("rg");
+        let zsh = Shell::Zsh.completions_dir().join("_rg");
+        let mut listed = files(archive.path()).unwrap();
+        listed.sort();
+        let mut expected = vec![
+            Path::new("bin/rg").to_owned(),
+            bash.clone(),
+            zsh.clone(),
+            Path::new("share/man/man1/rg.1").to_owned(),
+        ];
+        expected.sort();
+        assert_eq!(listed, expected);
+
+        let home = tempfile::tempdir().unwrap();
+        unpack(archive.path(), home.path()).unwrap();
+        for file in &expected {
+            assert!(home.path().join(file).is_file(), "{file:?} should exist");
+        }
+        assert_eq!(
+            fs::read_to_string(home.path().join(&bash)).unwrap(),
+            "completions/bash/rg"
+        );
+        assert!(!home.path().join("completions").exists());
+
+        let env = Shell::Bash.env(home.path());
+        assert!(env.contains(
+            &home
+                .path()
+                .join(Shell::Bash.completions_dir())
+                .display()
+                .to_string()
+        ));
+    }
+
+    #[test]
+    fn conflicting_completions() {
+        let sources = tempfile::tempdir().unwrap();
+        let source = sources.path().join("rg.bash");
+        fs::write(&source, "").unwrap();
+
+        // both files would be installed to the same path
+        let files_to_pack = [
+            TargetFile {
+                source: source.clone(),
+                dest: "completions/bash/rg".into(),
+            },
+            TargetFile {
+                source,
+                dest: Shell::Bash.completions_dir().join("rg"),
+            },
+        ];
+        assert!(pack(&files_to_pack, Vec::new()).is_err());
+    }
+}
@@ -1,27 +1,189 @@
-use std::{fs, io, path::PathBuf};
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{self, Read},
+    path::PathBuf,
+};
 
-use anyhow::{anyhow, Context};
+use anyhow::{anyhow, bail, Context};
+use model::GetOutput;
+use sha2::{Digest, Sha256};
+
+use crate::{
+    client::{Download, Resume},
+    digest::{copy_and_update, file_digest},
+};
+
+/// A cached package.
+pub struct Cached {
+    /// The path to the cached artifact.
+    pub path: PathBuf,
+    /// The output of the get operation that the artifact was downloaded with.
+    pub output: GetOutput,
+    /// The ETag of the response that the artifact was downloaded with, if any.
+    pub etag: Option<String>,
+}
+
+/// Returns the path to a cached package.
+fn path(name: &str, version: &str) -> PathBuf {
+    let filename = format!("{}-{}", name, version);
+    crate::dirs::armory_cache().join(filename)
+}
+
+/// Returns the path to a partially downloaded package.
+fn partial_path(name: &str, version: &str) -> PathBuf {
+    let filename = format!("{}-{}.partial", name, version);
+    crate::dirs::armory_cache().join(filename)
+}
+
+/// Returns the path to the recorded ETag of a partially downloaded package.
+fn partial_etag_path(name: &str, version: &str) -> PathBuf {
+    let filename = format!("{}-{}.partial.etag", name, version);
+    crate::dirs::armory_cache().join(filename)
+}
+
+/// Returns the path to the recorded metadata of a cached package.
+fn metadata_path(name: &str, version: &str) -> PathBuf {
+    let filename = format!("{}-{}.json", name, version);
+    crate::dirs::armory_cache().join(filename)
+}
+
+/// Returns the path to the recorded ETag of a cached package.
+fn etag_path(name: &str, version: &str) -> PathBuf {
+    let filename = format!("{}-{}.etag", name, version);
+    crate::dirs::armory_cache().join(filename)
+}
 
 /// Stores a package in the cache.
 ///
-/// Returns the path to the cached artifact.
-pub fn put(name: &str, version: &str, content: &[u8]) -> anyhow::Result<PathBuf> {
-    let filename = format!("{}-{}", name, version);
-    let path = crate::dirs::armory_cache().join(filename);
+/// The content is streamed to a partial file that is moved into place once the
+/// content has been read in full and its SHA-256 digest matches the checksum in
+/// the output, so an interrupted or corrupted download never leaves a bad
+/// artifact in the cache. The output and ETag are recorded alongside the
+/// artifact.
+///
+/// If the download resumes an interrupted one, the content is appended to the
+/// existing partial file and the checksum covers the content as a whole. The
+/// partial file is kept if the download is interrupted again, so that it can be
+/// resumed on a later attempt.
+pub fn put(download: Download<impl Read>, etag: Option<String>) -> anyhow::Result<Cached> {
+    let Download {
+        output,
+        offset,
+        mut content,
+    } = download;
+    let GetOutput { name, version, .. } = &output;
+
+    let path = path(name, version);
     fs::create_dir_all(crate::dirs::armory_cache()).context("failed to create cache dir")?;
-    fs::write(&path, content).with_context(|| format!("failed to cache content at {path:?}"))?;
-    Ok(path)
+
+    let partial = partial_path(name, version);
+    let partial_etag = partial_etag_path(name, version);
+    let mut hasher = Sha256::new();
+    let mut file = match offset {
+        0 => File::create(&partial)
+            .with_context(|| format!("failed to create cache file at {partial:?}"))?,
+        _ => {
+            let mut file = OpenOptions::new()
+                .read(true)
+                .append(true)
+                .open(&partial)
+                .with_context(|| format!("failed to open cache file at {partial:?}"))?;
+            let existing = copy_and_update(&mut file, &mut io::sink(), &mut hasher)
+                .with_context(|| format!("failed to read cache file at {partial:?}"))?;
+            if existing != offset {
+                fs::remove_file(&partial).ok();
+                fs::remove_file(&partial_etag).ok();
+                bail!("partial download of {name}-{version} changed while resuming it");
+            }
+            file
+        }
+    };
+
+    // the etag identifies the content that the partial file holds, so that the
+    // download is only resumed if the artifact has not changed since
+    match &etag {
+        Some(etag) => fs::write(&partial_etag, etag).context("failed to record partial etag")?,
+        None => {
+            fs::remove_file(&partial_etag).ok();
+        }
+    }
+
+    copy_and_update(&mut content, &mut file, &mut hasher)
+        .with_context(|| format!("failed to cache content at {partial:?}"))?;
+    let actual = format!("{:x}", hasher.finalize());
+
+    if actual != output.checksum {
+        fs::remove_file(&partial).ok();
+        fs::remove_file(&partial_etag).ok();
+        bail!(
+            "checksum mismatch for downloaded package {name}-{version}: expected {}, found {actual}",
+            output.checksum
+        );
+    }
+
+    let metadata = serde_json::to_vec(&output).context("failed to serialize metadata")?;
+    fs::write(metadata_path(name, version), metadata).context("failed to cache metadata")?;
+    match &etag {
+        Some(etag) => fs::write(etag_path(name, version), etag).context("failed to cache etag")?,
+        None => {
+            fs::remove_file(etag_path(name, version)).ok();
+        }
+    }
+    fs::rename(&partial, &path)
+        .with_context(|| format!("failed to move {partial:?} to {path:?}"))?;
+    fs::remove_file(&partial_etag).ok();
+
+    Ok(Cached { path, output, etag })
 }
 
-/// Loads a package from the cache.
+/// Looks up an interrupted download of a package.
 ///
-/// Returns the package content if it is cached.
-pub fn get(name: &str, version: &str) -> anyhow::Result<Option<Vec<u8>>> {
-    let filename = format!("{}-{}", name, version);
-    let path = crate::dirs::armory_cache().join(filename);
-    match fs::read(path) {
-        Ok(content) => Ok(Some(content)),
-        Err(e) if matches!(e.kind(), io::ErrorKind::NotFound) => Ok(None),
-        Err(e) => Err(anyhow!(e).context("failed to read cached content")),
+/// Returns the state of the download if part of the content was downloaded and
+/// the response that it came from had an ETag to resume it against.
+pub fn resume(name: &str, version: &str) -> Option<Resume> {
+    let offset = fs::metadata(partial_path(name, version)).ok()?.len();
+    if offset == 0 {
+        return None;
     }
+
+    let etag = fs::read_to_string(partial_etag_path(name, version)).ok()?;
+
+    Some(Resume { offset, etag })
+}
+
+/// Looks up a package in the cache.
+///
+/// The content of the cached artifact is verified against the checksum that
+/// was recorded when it was cached. Returns the cached package if it is cached,
+/// or an error if its content no longer matches the checksum. A corrupted
+/// artifact is evicted so that it is downloaded again next time.
+pub fn get(name: &str, version: &str) -> anyhow::Result<Option<Cached>> {
+    let path = path(name, version);
+    if !path.is_file() {
+        return Ok(None);
+    }
+
+    // artifacts cached before metadata was recorded cannot be verified, so
+    // they are treated as missing and downloaded again
+    let output: GetOutput = match fs::read(metadata_path(name, version)) {
+        Ok(metadata) => {
+            serde_json::from_slice(&metadata).context("cached metadata is malformed")?
+        }
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(anyhow!(e).context("failed to read cached metadata")),
+    };
+
+    let actual = file_digest(&path).context("failed to read cached content")?;
+
+    if actual != output.checksum {
+        fs::remove_file(&path).ok();
+        bail!(
+            "checksum mismatch for cached package {name}-{version}: expected {}, found {actual}; the corrupted artifact has been removed from the cache",
+            output.checksum
+        );
+    }
+
+    let etag = fs::read_to_string(etag_path(name, version)).ok();
+
+    Ok(Some(Cached { path, output, etag }))
 }
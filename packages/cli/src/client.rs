@@ -1,19 +1,75 @@
+use std::{fs::File, io::Read};
+
 use anyhow::{anyhow, Context};
+use log::debug;
 use model::{
-    ErrorInfo, GeneralError, GetError, GetInfoError, GetInfoInput, GetInfoOutput, GetInput,
-    GetOutput, ListError, ListInput, ListOutput, PublishError, PublishInput, PublishOutput,
+    AddOwnerError, AddOwnerInput, AddOwnerOutput, CreateTokenError, CreateTokenInput,
+    CreateTokenOutput, ErrorInfo, GeneralError, GetError, GetIndexError, GetInput, GetOutput,
+    ListError, ListInput, ListOutput, ListOwnersError, ListOwnersInput, ListOwnersOutput,
+    ListTokensError, ListTokensInput, ListTokensOutput, PublishError, PublishInput, PublishOutput,
+    RemoveOwnerError, RemoveOwnerInput, RemoveOwnerOutput, ResolveError, ResolveInput,
+    ResolveOutput, RevokeTokenError, RevokeTokenInput, RevokeTokenOutput, SearchError, SearchInput,
+    SearchOutput, TagError, TagInput, TagOutput, UnpublishError, UnpublishInput, UnpublishOutput,
+    YankError, YankInput, YankOutput,
+};
+use reqwest::{
+    blocking::{Body, Client as HttpClient, RequestBuilder, Response},
+    header::{
+        ACCEPT_ENCODING, CONTENT_ENCODING, CONTENT_RANGE, CONTENT_TYPE, ETAG, IF_NONE_MATCH,
+        IF_RANGE, RANGE,
+    },
+    StatusCode,
 };
-use reqwest::blocking::Client as HttpClient;
 use serde::{de::DeserializeOwned, Serialize};
 use thiserror::Error;
 
+use crate::responses;
+
 mod header {
     /// Indicates the success or failure of an operation.
     ///
     /// Should be set to `true` or `false`.
-    pub const OK: &'static str = "x-ok";
+    pub const OK: &str = "x-ok";
     /// The password to use for authentication.
-    pub const PASSWORD: &'static str = "x-password";
+    pub const PASSWORD: &str = "x-password";
+    /// The API token to use for authentication.
+    pub const TOKEN: &str = "x-token";
+    /// The JSON-encoded input of an operation that streams its request body.
+    pub const INPUT: &str = "x-input";
+    /// The JSON-encoded output of an operation that streams its response body.
+    pub const OUTPUT: &str = "x-output";
+}
+
+/// The result of a conditional request.
+pub enum Fetched<T> {
+    /// The resource has changed since it was last fetched.
+    Modified {
+        value: T,
+        /// The ETag of the resource, if the registry provided one.
+        etag: Option<String>,
+    },
+    /// The resource has not changed since it was last fetched.
+    NotModified,
+}
+
+/// A download of the content of an artifact.
+pub struct Download<R> {
+    /// The output of the get operation.
+    pub output: GetOutput,
+    /// The offset in the content that the download starts at.
+    ///
+    /// This is only nonzero if an interrupted download is being resumed.
+    pub offset: u64,
+    /// A reader that streams the content from the offset onwards.
+    pub content: R,
+}
+
+/// The state of an interrupted download.
+pub struct Resume {
+    /// The number of bytes that have already been downloaded.
+    pub offset: u64,
+    /// The ETag of the response that the download started with.
+    pub etag: String,
 }
 
 /// Client errors.
@@ -44,21 +100,32 @@ pub enum Error<T> {
 /// A client for the armory registry.
 pub struct Client {
     registry_url: String,
+    token: Option<String>,
     password: Option<String>,
     client: HttpClient,
 }
 
 impl Client {
     /// Creates a new client.
-    pub fn new(registry_url: String, password: Option<String>) -> Self {
+    ///
+    /// Requests are authenticated with the token if one is provided and with
+    /// the password otherwise. Requests do not time out once connected because
+    /// artifacts can be large and take a while to transfer.
+    pub fn new(registry_url: String, token: Option<String>, password: Option<String>) -> Self {
+        let client = HttpClient::builder()
+            .timeout(None)
+            .build()
+            .expect("client should build");
+
         Self {
             registry_url,
+            token,
             password,
-            client: HttpClient::new(),
+            client,
         }
     }
 
-    /// Sends a request.
+    /// Sends a request with a JSON body and parses the JSON response.
     fn send<Input, Output, Err>(&self, path: &str, input: Input) -> Result<Output, Error<Err>>
     where
         Input: Serialize,
@@ -67,16 +134,113 @@ impl Client {
     {
         let url = format!("{}{path}", self.registry_url);
 
-        let mut request = self.client.post(url).json(&input);
+        let response = self.execute(self.client.post(url).json(&input))?;
+
+        let output = response
+            .json::<Output>()
+            .context("output is malformed")
+            .map_err(Error::Transport)?;
+
+        Ok(output)
+    }
+
+    /// Sends a read request with the input in the query string and parses the
+    /// JSON response.
+    ///
+    /// Read requests use GET so that they can be cached. The last response to
+    /// each request is stored along with its ETag, and is reused if the
+    /// registry reports that it has not changed.
+    fn query<Input, Output, Err>(&self, path: &str, input: Input) -> Result<Output, Error<Err>>
+    where
+        Input: Serialize,
+        Output: DeserializeOwned,
+        Err: TryFrom<ErrorInfo, Error = anyhow::Error>,
+    {
+        let url = format!("{}{path}", self.registry_url);
+
+        let query = serde_json::to_string(&input)
+            .context("failed to serialize input")
+            .map_err(Error::Transport)?;
+        let key = responses::key(&[
+            &url,
+            &query,
+            self.token.as_deref().unwrap_or_default(),
+            self.password.as_deref().unwrap_or_default(),
+        ]);
+        let stored = responses::get(&key);
+
+        let request = self.client.get(url).query(&input);
+        let body =
+            match self.execute_conditional(request, stored.as_ref().map(|s| s.etag.as_str()))? {
+                Some(response) => {
+                    let etag = etag_of(&response);
+                    let body = response
+                        .bytes()
+                        .context("failed to read output")
+                        .map_err(Error::Transport)?
+                        .to_vec();
+                    if let Some(etag) = etag {
+                        if let Err(e) = responses::put(&key, &etag, &body) {
+                            debug!("failed to store response: {e:?}");
+                        }
+                    }
+                    body
+                }
+                None => {
+                    debug!("response to {path} is up to date");
+                    stored.expect("response should be stored").body
+                }
+            };
+
+        let output = serde_json::from_slice::<Output>(&body)
+            .context("output is malformed")
+            .map_err(Error::Transport)?;
+
+        Ok(output)
+    }
+
+    /// Executes a conditional request and checks the response for errors.
+    ///
+    /// Returns the raw response if the operation succeeded, or `None` if the
+    /// resource still matches the ETag of a previous response.
+    fn execute_conditional<Err>(
+        &self,
+        mut request: RequestBuilder,
+        etag: Option<&str>,
+    ) -> Result<Option<Response>, Error<Err>>
+    where
+        Err: TryFrom<ErrorInfo, Error = anyhow::Error>,
+    {
+        if let Some(etag) = etag {
+            request = request.header(IF_NONE_MATCH, etag);
+        }
+
+        let response = self.execute(request)?;
+
+        if response.status() == StatusCode::NOT_MODIFIED {
+            return Ok(None);
+        }
+
+        Ok(Some(response))
+    }
 
-        if let Some(password) = self.password.as_ref() {
+    /// Executes a request and checks the response for errors.
+    ///
+    /// Returns the raw response if the operation succeeded.
+    fn execute<Err>(&self, mut request: RequestBuilder) -> Result<Response, Error<Err>>
+    where
+        Err: TryFrom<ErrorInfo, Error = anyhow::Error>,
+    {
+        if let Some(token) = self.token.as_ref() {
+            request = request.header(header::TOKEN, token);
+        } else if let Some(password) = self.password.as_ref() {
             request = request.header(header::PASSWORD, password);
         }
 
         let response = request
             .send()
             .context("failed to send request")
-            .map_err(|e| Error::Transport(e))?;
+            .map_err(Error::Transport)?;
 
         let ok = {
             let header = response.headers().get(header::OK).map(|v| v.to_str());
@@ -95,7 +259,7 @@ impl Client {
             let error_info = response
                 .json::<ErrorInfo>()
                 .context("error message is malformed")
-                .map_err(|e| Error::Transport(e))?;
+                .map_err(Error::Transport)?;
 
             if let Ok(error) = TryInto::<GeneralError>::try_into(error_info.clone()) {
                 return Err(Error::General(error));
@@ -104,36 +268,277 @@ impl Client {
             let error: Err = error_info
                 .try_into()
                 .context("failed to parse error code")
-                .map_err(|e| Error::Transport(e))?;
+                .map_err(Error::Transport)?;
 
             return Err(Error::Specific(error));
         }
 
-        let output = response
-            .json::<Output>()
-            .context("output is malformed")
-            .map_err(|e| Error::Transport(e))?;
-
-        Ok(output)
+        Ok(response)
     }
 
     /// Publishes a package to the registry.
-    pub fn publish(&self, input: PublishInput) -> Result<PublishOutput, Error<PublishError>> {
-        self.send("/publish", input)
+    ///
+    /// The content of the artifact is streamed from the provided file and
+    /// compressed with zstd on the way.
+    pub fn publish(
+        &self,
+        input: PublishInput,
+        content: File,
+    ) -> Result<PublishOutput, Error<PublishError>> {
+        let url = format!("{}/publish", self.registry_url);
+
+        let input = serde_json::to_vec(&input)
+            .context("failed to serialize input")
+            .map_err(Error::Transport)?;
+
+        let content = zstd::stream::read::Encoder::new(content, 0)
+            .context("failed to compress content")
+            .map_err(Error::Transport)?;
+
+        let request = self
+            .client
+            .post(url)
+            .header(header::INPUT, input)
+            .header(CONTENT_TYPE, "application/octet-stream")
+            .header(CONTENT_ENCODING, "zstd")
+            .body(Body::new(content));
+
+        self.execute(request)?
+            .json::<PublishOutput>()
+            .context("output is malformed")
+            .map_err(Error::Transport)
     }
 
     /// Gets a package from the registry.
-    pub fn get(&self, input: GetInput) -> Result<GetOutput, Error<GetError>> {
-        self.send("/get", input)
+    ///
+    /// Returns the output along with a reader that streams the content of the
+    /// artifact. If the ETag of a previous response is provided, the package is
+    /// only returned if it has changed since. The content is requested with
+    /// zstd compression and decompressed as it is read.
+    ///
+    /// If an interrupted download is provided, only the rest of the content is
+    /// requested. The registry sends the full content instead if the artifact
+    /// has changed since the download started, so the offset of the returned
+    /// download must be checked.
+    pub fn get(
+        &self,
+        input: GetInput,
+        etag: Option<&str>,
+        resume: Option<&Resume>,
+    ) -> Result<Fetched<Download<Box<dyn Read>>>, Error<GetError>> {
+        let url = format!("{}/get", self.registry_url);
+
+        let mut request = self
+            .client
+            .get(url)
+            .query(&input)
+            .header(ACCEPT_ENCODING, "zstd");
+        if let Some(resume) = resume {
+            request = request
+                .header(RANGE, format!("bytes={}-", resume.offset))
+                .header(IF_RANGE, uncompressed_etag(&resume.etag));
+        }
+
+        let Some(response) = self.execute_conditional(request, etag)? else {
+            return Ok(Fetched::NotModified);
+        };
+
+        let offset = match response.status() {
+            StatusCode::PARTIAL_CONTENT => {
+                let offset = range_start(&response).map_err(Error::Transport)?;
+                if resume.is_none_or(|resume| resume.offset != offset) {
+                    return Err(Error::Transport(anyhow!(
+                        "registry returned an unexpected range starting at {offset}"
+                    )));
+                }
+                offset
+            }
+            _ => 0,
+        };
+
+        let output = response
+            .headers()
+            .get(header::OUTPUT)
+            .context("'output' response header is missing")
+            .and_then(|v| {
+                serde_json::from_slice::<GetOutput>(v.as_bytes()).context("output is malformed")
+            })
+            .map_err(Error::Transport)?;
+
+        let etag = etag_of(&response);
+
+        let content: Box<dyn Read> = match response.headers().get(CONTENT_ENCODING) {
+            None => Box::new(response),
+            Some(encoding) if encoding == "zstd" => Box::new(
+                zstd::stream::read::Decoder::new(response)
+                    .context("failed to decompress content")
+                    .map_err(Error::Transport)?,
+            ),
+            Some(encoding) => {
+                return Err(Error::Transport(anyhow!(
+                    "registry returned unsupported content encoding {encoding:?}"
+                )))
+            }
+        };
+
+        Ok(Fetched::Modified {
+            value: Download {
+                output,
+                offset,
+                content,
+            },
+            etag,
+        })
     }
 
-    /// Gets information about a package from the registry.
-    pub fn get_info(&self, input: GetInfoInput) -> Result<GetInfoOutput, Error<GetInfoError>> {
-        self.send("/get-info", input)
+    /// Resolves a version requirement to a version of a package.
+    pub fn resolve(&self, input: ResolveInput) -> Result<ResolveOutput, Error<ResolveError>> {
+        self.query("/resolve", input)
+    }
+
+    /// Gets the index of a package.
+    ///
+    /// If the ETag of a previous response is provided, the index is only
+    /// returned if it has changed since.
+    pub fn get_index(
+        &self,
+        name: &str,
+        etag: Option<&str>,
+    ) -> Result<Fetched<String>, Error<GetIndexError>> {
+        let url = format!("{}/index/{name}", self.registry_url);
+
+        let Some(response) = self.execute_conditional(self.client.get(url), etag)? else {
+            return Ok(Fetched::NotModified);
+        };
+
+        let etag = etag_of(&response);
+
+        let value = response
+            .text()
+            .context("index is malformed")
+            .map_err(Error::Transport)?;
+
+        Ok(Fetched::Modified { value, etag })
     }
 
     /// Lists packages in the registry.
     pub fn list(&self, input: ListInput) -> Result<ListOutput, Error<ListError>> {
-        self.send("/list", input)
+        self.query("/list", input)
+    }
+
+    /// Creates an API token.
+    pub fn create_token(
+        &self,
+        input: CreateTokenInput,
+    ) -> Result<CreateTokenOutput, Error<CreateTokenError>> {
+        self.send("/create-token", input)
+    }
+
+    /// Lists API tokens.
+    pub fn list_tokens(
+        &self,
+        input: ListTokensInput,
+    ) -> Result<ListTokensOutput, Error<ListTokensError>> {
+        self.send("/list-tokens", input)
+    }
+
+    /// Revokes an API token.
+    pub fn revoke_token(
+        &self,
+        input: RevokeTokenInput,
+    ) -> Result<RevokeTokenOutput, Error<RevokeTokenError>> {
+        self.send("/revoke-token", input)
+    }
+
+    /// Adds an owner to a package.
+    pub fn add_owner(&self, input: AddOwnerInput) -> Result<AddOwnerOutput, Error<AddOwnerError>> {
+        self.send("/add-owner", input)
+    }
+
+    /// Removes an owner from a package.
+    pub fn remove_owner(
+        &self,
+        input: RemoveOwnerInput,
+    ) -> Result<RemoveOwnerOutput, Error<RemoveOwnerError>> {
+        self.send("/remove-owner", input)
+    }
+
+    /// Lists the owners of a package.
+    pub fn list_owners(
+        &self,
+        input: ListOwnersInput,
+    ) -> Result<ListOwnersOutput, Error<ListOwnersError>> {
+        self.query("/list-owners", input)
+    }
+
+    /// Yanks a version of a package, or undoes a previous yank.
+    pub fn yank(&self, input: YankInput) -> Result<YankOutput, Error<YankError>> {
+        self.send("/yank", input)
+    }
+
+    /// Points a tag of a package at a version, or removes the tag.
+    pub fn tag(&self, input: TagInput) -> Result<TagOutput, Error<TagError>> {
+        self.send("/tag", input)
+    }
+
+    /// Removes a package, a version of a package, or a single artifact.
+    pub fn unpublish(
+        &self,
+        input: UnpublishInput,
+    ) -> Result<UnpublishOutput, Error<UnpublishError>> {
+        self.send("/unpublish", input)
+    }
+
+    /// Searches for packages in the registry.
+    pub fn search(&self, input: SearchInput) -> Result<SearchOutput, Error<SearchError>> {
+        self.query("/search", input)
+    }
+}
+
+/// Returns the ETag of the uncompressed representation of an artifact, given
+/// the ETag of either representation.
+///
+/// The registry tags zstd-encoded responses with a `-zstd` suffix. Ranges always
+/// refer to the uncompressed artifact, so `If-Range` must use its ETag.
+fn uncompressed_etag(etag: &str) -> String {
+    match etag.strip_suffix("-zstd\"") {
+        Some(tag) => format!("{tag}\""),
+        None => etag.to_owned(),
+    }
+}
+
+/// Returns the ETag of a response, if it has one.
+fn etag_of(response: &Response) -> Option<String> {
+    response
+        .headers()
+        .get(ETAG)
+        .and_then(|v| v.to_str().ok())
+        .map(|v| v.to_owned())
+}
+
+/// Returns the offset that the content of a partial response starts at.
+fn range_start(response: &Response) -> anyhow::Result<u64> {
+    let range = response
+        .headers()
+        .get(CONTENT_RANGE)
+        .context("'content-range' response header is missing")?
+        .to_str()
+        .context("'content-range' response header is malformed")?;
+
+    range
+        .strip_prefix("bytes ")
+        .and_then(|range| range.split_once('-'))
+        .and_then(|(start, _)| start.parse().ok())
+        .context("'content-range' response header is malformed")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::uncompressed_etag;
+
+    #[test]
+    fn uncompressed_etags() {
+        assert_eq!(uncompressed_etag("\"abc-zstd\""), "\"abc\"");
+        assert_eq!(uncompressed_etag("\"abc\""), "\"abc\"");
     }
 }
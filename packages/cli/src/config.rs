@@ -9,9 +9,21 @@ pub struct Config {
     /// The URL of the registry.
     #[config(env = "ARMORY_URL")]
     pub registry_url: String,
+    /// The API token to use for authentication.
+    ///
+    /// Takes precedence over the password if both are set.
+    #[config(env = "ARMORY_TOKEN")]
+    pub token: Option<String>,
     /// The password to use for authentication.
     #[config(env = "ARMORY_PASSWORD")]
     pub password: Option<String>,
+    /// Public keys of publishers whose signatures are trusted, encoded as hex.
+    #[config(default = [])]
+    pub trusted_keys: Vec<String>,
+    /// Whether to refuse to install packages that are not signed by a trusted
+    /// key.
+    #[config(env = "ARMORY_REQUIRE_SIGNATURES", default = false)]
+    pub require_signatures: bool,
 }
 
 impl Config {
@@ -0,0 +1,49 @@
+use std::{
+    fs::File,
+    io::{self, Read, Write},
+    path::Path,
+};
+
+use anyhow::Context;
+use sha2::{Digest, Sha256};
+
+/// Copies content from a reader to a writer.
+///
+/// Returns the SHA-256 digest of the content, encoded as lowercase hex.
+pub fn copy_and_hash(reader: &mut impl Read, writer: &mut impl Write) -> io::Result<String> {
+    let mut hasher = Sha256::new();
+    copy_and_update(reader, writer, &mut hasher)?;
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// Copies content from a reader to a writer, feeding it to a hasher on the way.
+///
+/// This allows content that arrives in several pieces to be hashed as a whole.
+/// Returns the number of bytes copied.
+pub fn copy_and_update(
+    reader: &mut impl Read,
+    writer: &mut impl Write,
+    hasher: &mut Sha256,
+) -> io::Result<u64> {
+    let mut copied = 0;
+    let mut buffer = vec![0; 64 * 1024];
+    loop {
+        let read = match reader.read(&mut buffer) {
+            Ok(0) => break,
+            Ok(read) => read,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        };
+        hasher.update(&buffer[..read]);
+        writer.write_all(&buffer[..read])?;
+        copied += read as u64;
+    }
+    writer.flush()?;
+    Ok(copied)
+}
+
+/// Computes the SHA-256 digest of a file, encoded as lowercase hex.
+pub fn file_digest(path: &Path) -> anyhow::Result<String> {
+    let mut file = File::open(path).with_context(|| format!("failed to open {path:?}"))?;
+    copy_and_hash(&mut file, &mut io::sink()).with_context(|| format!("failed to read {path:?}"))
+}
@@ -15,3 +15,10 @@ pub fn armory_home() -> PathBuf {
 pub fn armory_cache() -> PathBuf {
     armory_home().join("cache")
 }
+
+/// Returns the Armory index directory.
+///
+/// It is located at ~/.armory/index
+pub fn armory_index() -> PathBuf {
+    armory_home().join("index")
+}
@@ -0,0 +1,176 @@
+use std::{fs, io, path::PathBuf};
+
+use anyhow::{anyhow, Context};
+use log::{debug, warn};
+use model::{GetIndexError, IndexEntry, Triple};
+use utils::{requirement::ParseReqError, validation::validate_tag, VersionReq};
+
+use crate::client::{Client, Error, Fetched};
+
+/// The local copy of the index of a package.
+pub struct PackageIndex {
+    /// The name of the package.
+    pub name: String,
+    /// The entries of the index in ascending version order.
+    pub entries: Vec<IndexEntry>,
+}
+
+impl PackageIndex {
+    /// Resolves a version requirement or tag for a triple.
+    ///
+    /// This mirrors how the registry resolves requirements, and is used when it
+    /// cannot be reached.
+    pub fn resolve(
+        &self,
+        requirement: &str,
+        triple: &Triple,
+    ) -> Result<Option<&IndexEntry>, ParseReqError> {
+        let available = |entry: &&IndexEntry| entry.targets.iter().any(|t| t.triple == *triple);
+
+        if let Some(entry) = self
+            .entries
+            .iter()
+            .find(|entry| entry.tags.iter().any(|tag| tag == requirement))
+        {
+            return Ok((available(&entry) && !entry.yanked).then_some(entry));
+        }
+
+        if let Some(entry) = self
+            .entries
+            .iter()
+            .filter(available)
+            .find(|entry| entry.version == requirement)
+        {
+            return Ok(Some(entry));
+        }
+
+        // a tag that does not exist matches nothing
+        if validate_tag(requirement).is_ok() {
+            return Ok(None);
+        }
+
+        let requirement = VersionReq::parse(requirement)?;
+        let version = requirement.best_match(
+            self.entries
+                .iter()
+                .filter(available)
+                .filter(|entry| !entry.yanked)
+                .map(|entry| entry.version.as_str()),
+        );
+
+        Ok(version.and_then(|version| self.entries.iter().find(|e| e.version == version)))
+    }
+
+    /// Returns the latest version that is available for a triple.
+    ///
+    /// Yanked versions are skipped.
+    pub fn latest(&self, triple: &Triple) -> Option<&IndexEntry> {
+        self.entries.iter().rev().find(|entry| {
+            !entry.yanked && entry.targets.iter().any(|target| target.triple == *triple)
+        })
+    }
+}
+
+/// Returns the path to the local copy of the index of a package.
+fn path(name: &str) -> PathBuf {
+    let filename = format!("{}.jsonl", name);
+    crate::dirs::armory_index().join(filename)
+}
+
+/// Returns the path to the recorded ETag of the index of a package.
+fn etag_path(name: &str) -> PathBuf {
+    let filename = format!("{}.etag", name);
+    crate::dirs::armory_index().join(filename)
+}
+
+/// Loads the index of a package.
+///
+/// The local copy is refreshed from the registry first, using a conditional
+/// request so that an unchanged index is not downloaded again. If the registry
+/// cannot be reached, the local copy is used as is.
+pub fn load(client: &Client, name: &str) -> anyhow::Result<PackageIndex> {
+    let cached = path(name).is_file();
+    let etag = match cached {
+        true => fs::read_to_string(etag_path(name)).ok(),
+        false => None,
+    };
+
+    match client.get_index(name, etag.as_deref()) {
+        Ok(Fetched::NotModified) => debug!("index of {name} is up to date"),
+        Ok(Fetched::Modified { value, etag }) => store(name, &value, etag.as_deref())?,
+        Err(Error::Transport(e)) if cached => {
+            warn!("failed to reach registry; using cached index of {name}");
+            debug!("{e:?}");
+        }
+        Err(e) => {
+            if matches!(e, Error::Specific(GetIndexError::PackageNotFound)) {
+                remove(name);
+            }
+            return Err(e).context("'get_index' request failed");
+        }
+    }
+
+    read(name)
+}
+
+/// Loads the local copies of every package index.
+///
+/// The copies are not refreshed, so they may be out of date.
+pub fn cached() -> anyhow::Result<Vec<PackageIndex>> {
+    let entries = match fs::read_dir(crate::dirs::armory_index()) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(anyhow!(e).context("failed to read index dir")),
+    };
+
+    let mut names = Vec::new();
+    for entry in entries {
+        let entry = entry.context("failed to read index dir")?;
+        let file_name = entry.file_name();
+        if let Some(name) = file_name.to_string_lossy().strip_suffix(".jsonl") {
+            names.push(name.to_owned());
+        }
+    }
+    names.sort();
+
+    names.iter().map(|name| read(name)).collect()
+}
+
+/// Reads the local copy of the index of a package.
+fn read(name: &str) -> anyhow::Result<PackageIndex> {
+    let content = fs::read_to_string(path(name))
+        .with_context(|| format!("failed to read cached index of {name}"))?;
+
+    let entries = content
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(serde_json::from_str)
+        .collect::<Result<_, _>>()
+        .with_context(|| format!("cached index of {name} is malformed"))?;
+
+    Ok(PackageIndex {
+        name: name.to_owned(),
+        entries,
+    })
+}
+
+/// Replaces the local copy of the index of a package.
+fn store(name: &str, content: &str, etag: Option<&str>) -> anyhow::Result<()> {
+    fs::create_dir_all(crate::dirs::armory_index()).context("failed to create index dir")?;
+    fs::write(path(name), content).context("failed to cache index")?;
+
+    match etag {
+        Some(etag) => fs::write(etag_path(name), etag).context("failed to cache index etag")?,
+        None => {
+            fs::remove_file(etag_path(name)).ok();
+        }
+    }
+
+    Ok(())
+}
+
+/// Removes the local copy of the index of a package.
+fn remove(name: &str) {
+    fs::remove_file(path(name)).ok();
+    fs::remove_file(etag_path(name)).ok();
+}
@@ -1,4 +1,7 @@
-use std::{fs, path::PathBuf};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
 
 use anyhow::Context;
 use serde::{Deserialize, Serialize};
@@ -18,6 +21,62 @@ pub struct PackageRecord {
     pub name: String,
     /// The version of the package.
     pub version: String,
+    /// The version requirement that the package was installed with, if any.
+    ///
+    /// Upgrades stay within the requirement. Packages installed at the latest
+    /// version or at an exact version have no requirement.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub requirement: Option<String>,
+    /// The tag that the package was installed from, if any.
+    ///
+    /// Upgrades follow the version that the tag points to.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub channel: Option<String>,
+    /// Whether the package is pinned.
+    ///
+    /// Upgrades skip pinned packages.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub pinned: bool,
+    /// The files installed by the package, relative to the armory home.
+    ///
+    /// Packages installed before files were tracked have no recorded files.
+    #[serde(default)]
+    pub files: Vec<PathBuf>,
+}
+
+impl PackageRecord {
+    /// Returns the files installed by the package, relative to the armory home.
+    ///
+    /// Packages without recorded files are assumed to have installed a single
+    /// binary.
+    pub fn installed_files(&self) -> Vec<PathBuf> {
+        match self.files.is_empty() {
+            true => vec![binary_path(&self.name)],
+            false => self.files.clone(),
+        }
+    }
+
+    /// Returns the names of the commands that the package provides.
+    pub fn commands(&self) -> Vec<String> {
+        self.installed_files()
+            .iter()
+            .filter(|file| file.parent() == Some(Path::new("bin")))
+            .filter_map(|file| file.file_stem())
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .collect()
+    }
+}
+
+/// Returns the path that the binary of a package is installed to, relative to
+/// the armory home.
+pub fn binary_path(name: &str) -> PathBuf {
+    #[cfg(unix)]
+    let path = Path::new("bin").join(name);
+
+    #[cfg(windows)]
+    let path = Path::new("bin").join(format!("{}.exe", name));
+
+    path
 }
 
 impl InstallManifest {
@@ -37,7 +96,7 @@ impl InstallManifest {
             return Ok(manifest);
         }
         let manifest = Self::default();
-        return Ok(manifest);
+        Ok(manifest)
     }
 
     /// List installed packages.
@@ -45,13 +104,50 @@ impl InstallManifest {
         &self.packages
     }
 
-    /// Adds a package to the manifest.
+    /// Looks up an installed package.
+    pub fn package(&self, name: &str) -> Option<&PackageRecord> {
+        self.packages.iter().find(|package| package.name == name)
+    }
+
+    /// Looks up an installed package for modification.
+    pub fn package_mut(&mut self, name: &str) -> Option<&mut PackageRecord> {
+        self.packages
+            .iter_mut()
+            .find(|package| package.name == name)
+    }
+
+    /// Looks up the package that installed a file.
+    ///
+    /// The path is relative to the armory home.
+    pub fn owner(&self, file: &Path) -> Option<&PackageRecord> {
+        self.packages
+            .iter()
+            .find(|package| package.installed_files().iter().any(|f| f == file))
+    }
+
+    /// Adds a package to the manifest along with the files that it installed
+    /// and the requirement or channel that it was installed with.
     ///
     /// If a different version of the package is already in the manifest, it is
-    /// overwritten with the new version.
-    pub fn add_package(&mut self, name: String, version: String) {
+    /// overwritten with the new version. A pinned package stays pinned.
+    pub fn add_package(
+        &mut self,
+        name: String,
+        version: String,
+        requirement: Option<String>,
+        channel: Option<String>,
+        files: Vec<PathBuf>,
+    ) {
+        let pinned = self.package(&name).is_some_and(|package| package.pinned);
         self.remove_package(&name);
-        self.packages.push(PackageRecord { name, version });
+        self.packages.push(PackageRecord {
+            name,
+            version,
+            requirement,
+            channel,
+            pinned,
+            files,
+        });
     }
 
     /// Removes a package from the manifest.
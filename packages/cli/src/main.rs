@@ -1,31 +1,47 @@
+mod archive;
 mod cache;
 mod client;
 mod config;
+mod digest;
 mod dirs;
+mod index;
 mod install_manifest;
 mod package_manifest;
+mod responses;
+mod shell;
+mod signing;
 mod target;
 
 use std::{
     cmp::Ordering,
-    collections::HashMap,
-    fs::{self},
+    fs::{self, File},
+    io,
+    path::Path,
     str::FromStr,
+    time::{Duration, UNIX_EPOCH},
 };
 
 use anyhow::{bail, Context};
-use base64::{prelude::BASE64_STANDARD, Engine};
 use clap::{ArgAction, CommandFactory, Parser, Subcommand};
-use client::Client;
+use client::{Client, Fetched};
 use colored::{Color, Colorize};
 use dialoguer::{Confirm, Password};
 use env_logger::fmt::Formatter;
 use install_manifest::InstallManifest;
-use log::{error, info};
-use model::{GetInfoInput, GetInput, ListInput, PublishInput, Triple};
-use package_manifest::PackageManifest;
+use log::{debug, error, info, warn};
+use model::{
+    AddOwnerInput, CreateTokenInput, Format, GetInput, GetOutput, ListInput, ListOwnersInput,
+    ListTokensInput, Metadata, PackageSummary, PublishInput, RemoveOwnerInput, ResolveError,
+    ResolveInput, RevokeTokenInput, Scope, SearchInput, TagInput, Triple, UnpublishInput,
+    YankInput,
+};
+use package_manifest::{Artifact, PackageManifest};
+use shell::Shell;
 use std::io::Write;
-use utils::sort_versions;
+use utils::{
+    validation::{validate_name, validate_tag},
+    Version, VersionReq,
+};
 
 use crate::config::Config;
 
@@ -44,8 +60,11 @@ enum Command {
     /// Package information is sourced from _armory.toml_, a package manifest
     /// file, located in the current directory. The file should contain a
     /// `package` section with `name` and `version`. It should also contain one
-    /// or more [[target]] sections with a `triple` and the `path` to the binary for
-    /// that triple.
+    /// or more [[target]] sections with a `triple` and either the `path` to the
+    /// binary or the named `binaries` to publish for that triple, along with
+    /// any other `files`. Man pages and
+    /// completion scripts listed in the `package` section are published with
+    /// every target.
     ///
     /// The following triples are supported:
     ///
@@ -71,13 +90,23 @@ enum Command {
         id: Identifier,
         /// The version of the package.
         ///
-        /// If not specified, the latest version of the package is installed.
-        /// You can use this flag or specify a version in the identifier, but
-        /// you cannot use both methods at the same time.
+        /// This can be an exact version such as `1.2.3` or a requirement such
+        /// as `^1.2`, `~1.4`, `>=2, <3`, or `1`, in which case the highest
+        /// version that satisfies it is installed and later upgrades stay
+        /// within it. It can also be a tag such as `beta`, in which case the
+        /// version that the tag points to is installed and later upgrades
+        /// follow the tag. If not specified, the latest version of the package
+        /// is installed. You can use this flag or specify a version in the
+        /// identifier, but you cannot use both methods at the same time.
         #[arg(long)]
         version: Option<String>,
     },
     /// Installs the latest version of all installed packages.
+    ///
+    /// Packages that were installed with a version requirement are upgraded
+    /// to the latest version that satisfies it, and packages that were
+    /// installed from a tag are moved to the version that the tag points to.
+    /// Pinned packages are skipped.
     Upgrade,
     /// List available packages.
     ///
@@ -87,6 +116,23 @@ enum Command {
         #[arg(long, default_value_t = false)]
         installed: bool,
     },
+    /// Show information about a package.
+    ///
+    /// Lists every published version of the package along with the platforms
+    /// it is available for, and the size, publish time, and checksum of each
+    /// artifact. Artifacts for the current platform are marked with `*`.
+    Info {
+        /// The name of the package.
+        name: String,
+    },
+    /// Search for packages.
+    ///
+    /// Matches the query against the names, keywords, and descriptions of
+    /// packages that are available for the current platform.
+    Search {
+        /// The search query.
+        query: String,
+    },
     /// Uninstall a package.
     Uninstall {
         /// The name of the package.
@@ -98,8 +144,179 @@ enum Command {
         #[arg(long = "non-interactive",  default_value_t = true, action = ArgAction::SetFalse)]
         interactive: bool,
     },
+    /// Pin an installed package so that upgrades skip it.
+    ///
+    /// The package stays pinned when it is reinstalled, until it is unpinned.
+    Pin {
+        /// The name of the package.
+        name: String,
+        /// The version to pin the package at.
+        ///
+        /// If specified, this version is installed before the package is
+        /// pinned. It can be a version requirement or a tag, as with `armory
+        /// install`. If not specified, the package is pinned at the installed
+        /// version.
+        version: Option<String>,
+    },
+    /// Unpin a package so that upgrades include it again.
+    Unpin {
+        /// The name of the package.
+        name: String,
+    },
+    /// Yank a version of a package.
+    ///
+    /// Yanked versions are no longer installed as the latest version of a
+    /// package, but can still be installed by specifying the version
+    /// explicitly. Only owners of the package can yank its versions.
+    Yank {
+        /// The identifier of the package.
+        #[arg(value_name = "PACKAGE@VERSION")]
+        id: Identifier,
+        /// Undo a previous yank. (default: false)
+        #[arg(long, default_value_t = false)]
+        undo: bool,
+    },
+    /// Point a tag of a package at a version.
+    ///
+    /// Tags such as `stable`, `beta`, and `nightly` name release channels.
+    /// Installing `PACKAGE@TAG` installs the version that the tag points to,
+    /// and later upgrades follow the tag. Only owners of the package can move
+    /// its tags.
+    Tag {
+        /// The identifier of the package.
+        #[arg(value_name = "PACKAGE@VERSION")]
+        id: Identifier,
+        /// The name of the tag.
+        tag: String,
+        /// Remove the tag instead. (default: false)
+        #[arg(long, default_value_t = false)]
+        remove: bool,
+    },
+    /// Remove a package from the registry.
+    ///
+    /// Removes the whole package, a single version if one is specified, or a
+    /// single artifact if a triple is specified as well. This cannot be undone
+    /// and requires credentials with the admin scope. Consider yanking a
+    /// version instead.
+    Unpublish {
+        /// The identifier of the package.
+        #[arg(value_name = "PACKAGE[@VERSION]")]
+        id: Identifier,
+        /// Only remove the artifact for this triple.
+        #[arg(long)]
+        triple: Option<Triple>,
+        /// Do not prompt for input.
+        #[arg(long = "non-interactive",  default_value_t = true, action = ArgAction::SetFalse)]
+        interactive: bool,
+    },
     /// Set up registry credentials.
-    Login,
+    ///
+    /// Prompts for an API token and saves it to the config file. Tokens are
+    /// issued by a registry administrator using `armory token create`.
+    Login {
+        /// Save the shared registry password instead of a token.
+        #[arg(long, default_value_t = false)]
+        password: bool,
+    },
+    /// Manage the owners of a package.
+    ///
+    /// Only owners of a package can publish new versions of it. The first user
+    /// to publish a package becomes its owner.
+    Owner {
+        #[command(subcommand)]
+        command: OwnerCommand,
+    },
+    /// Manage API tokens for the registry.
+    ///
+    /// These commands require credentials with the admin scope.
+    Token {
+        #[command(subcommand)]
+        command: TokenCommand,
+    },
+    /// Print a script that sets up a shell to use installed packages.
+    ///
+    /// The script adds ~/.armory/bin to PATH and ~/.armory/share/man to
+    /// MANPATH, and loads the completion scripts of installed packages. Add
+    /// `eval "$(armory env)"` to your shell profile to run it in every shell
+    /// (for fish, `armory env | source`). In zsh, run it before `compinit`.
+    Env {
+        /// The shell to print the script for.
+        ///
+        /// If not specified, the shell is detected from the SHELL environment
+        /// variable.
+        #[arg(long)]
+        shell: Option<Shell>,
+    },
+    /// Manage the key used to sign published packages.
+    ///
+    /// When a signing key exists, `armory publish` signs every artifact with
+    /// it. Share the public key with the people who install your packages so
+    /// that they can add it to `trusted_keys` in their config.
+    Key {
+        #[command(subcommand)]
+        command: KeyCommand,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum OwnerCommand {
+    /// Add an owner to a package.
+    Add {
+        /// The name of the package.
+        package: String,
+        /// The user to add as an owner.
+        user: String,
+    },
+    /// Remove an owner from a package.
+    Remove {
+        /// The name of the package.
+        package: String,
+        /// The user to remove as an owner.
+        user: String,
+    },
+    /// List the owners of a package.
+    List {
+        /// The name of the package.
+        package: String,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum TokenCommand {
+    /// Create a token.
+    ///
+    /// The token is printed once and cannot be retrieved again.
+    Create {
+        /// The user that the token belongs to.
+        user: String,
+        /// A scope to grant to the token: read, publish, or admin.
+        ///
+        /// Can be specified more than once. The publish scope implies read and
+        /// the admin scope implies all other scopes.
+        #[arg(long = "scope", value_name = "SCOPE", required = true)]
+        scopes: Vec<Scope>,
+    },
+    /// List tokens.
+    List,
+    /// Revoke a token.
+    Revoke {
+        /// The identifier of the token.
+        id: String,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum KeyCommand {
+    /// Generate a new signing key.
+    ///
+    /// The key is saved to ~/.armory/signing.key and its public key is printed.
+    Generate {
+        /// Replace the existing signing key, if there is one.
+        #[arg(long, default_value_t = false)]
+        force: bool,
+    },
+    /// Print the public key of the signing key.
+    Show,
 }
 
 /// A package identifier.
@@ -113,7 +330,7 @@ impl FromStr for Identifier {
     type Err = anyhow::Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut parts = s.split("@");
+        let mut parts = s.split('@');
         let name = parts
             .next()
             .expect("should be at least one part")
@@ -122,6 +339,7 @@ impl FromStr for Identifier {
         if parts.count() > 0 {
             bail!("too many components in package identifier");
         }
+        validate_name(&name)?;
         let identifier = Identifier { name, version };
         Ok(identifier)
     }
@@ -150,8 +368,23 @@ fn main() {
         Command::Install { id, version } => install(id, version, config),
         Command::List { installed } => list(config, installed),
         Command::Upgrade => upgrade(config),
+        Command::Info { name } => package_info(config, name),
+        Command::Search { query } => search(config, query),
         Command::Uninstall { name, interactive } => uninstall(name, interactive),
-        Command::Login => login(),
+        Command::Pin { name, version } => pin(config, name, version),
+        Command::Unpin { name } => unpin(name),
+        Command::Yank { id, undo } => yank(config, id, undo),
+        Command::Tag { id, tag, remove } => tag_version(config, id, tag, remove),
+        Command::Unpublish {
+            id,
+            triple,
+            interactive,
+        } => unpublish(config, id, triple, interactive),
+        Command::Login { password } => login(password),
+        Command::Owner { command } => owner(config, command),
+        Command::Token { command } => token(config, command),
+        Command::Key { command } => key(command),
+        Command::Env { shell } => env(shell),
     };
 
     if let Err(e) = result {
@@ -217,36 +450,79 @@ fn publish(config: Config, triple: Option<Triple>) -> anyhow::Result<()> {
         },
     };
 
-    if !target.path.is_file() {
-        bail!("binary does not exist at {}", target.path.display());
-    }
+    // archives are packed into a temporary file so that they can be hashed
+    // and streamed like a binary
+    let (path, format, _archive) = match package.artifact(target)? {
+        Artifact::Binary(path) => {
+            if !path.is_file() {
+                bail!("binary does not exist at {}", path.display());
+            }
 
-    info!(
-        "publishing {}-{}-{} | binary: {}",
-        package.name,
-        package.version,
-        target.triple,
-        target.path.display()
-    );
+            info!(
+                "publishing {}-{}-{} | binary: {}",
+                package.name,
+                package.version,
+                target.triple,
+                path.display()
+            );
 
-    let content = {
-        let bytes = fs::read(&target.path).context("failed to load binary")?;
-        let encoded = BASE64_STANDARD.encode(bytes);
-        encoded
+            (path.to_owned(), Format::Binary, None)
+        }
+        Artifact::Archive(files) => {
+            info!(
+                "publishing {}-{}-{} | archive: {} files",
+                package.name,
+                package.version,
+                target.triple,
+                files.len()
+            );
+
+            let mut archive = tempfile::NamedTempFile::new().context("failed to create archive")?;
+            archive::pack(&files, archive.as_file_mut())?;
+
+            (archive.path().to_owned(), Format::Archive, Some(archive))
+        }
     };
 
+    let signature = match signing::load()? {
+        Some(key) => {
+            let checksum = digest::file_digest(&path).context("failed to hash artifact")?;
+            Some(signing::sign(
+                &key,
+                &package.name,
+                &package.version,
+                &target.triple,
+                &checksum,
+            ))
+        }
+        None => {
+            info!("no signing key found; publishing unsigned artifact");
+            None
+        }
+    };
+
+    let content = File::open(&path).context("failed to open artifact")?;
+
     let input = PublishInput {
         name: package.name.clone(),
         version: package.version.clone(),
         triple: target.triple.clone(),
-        content,
+        signature,
+        metadata: package.metadata(),
+        format,
     };
 
-    let client = Client::new(config.registry_url, config.password);
-    client.publish(input).context("'publish' request failed")?;
+    let client = Client::new(config.registry_url, config.token, config.password);
+    let output = client
+        .publish(input, content)
+        .context("'publish' request failed")?;
     info!(
-        "published {}-{}-{}",
-        package.name, package.version, target.triple
+        "published {}-{}-{} ({}, {} compressed)",
+        package.name,
+        package.version,
+        target.triple,
+        format_size(output.size),
+        format_size(output.compressed_size)
     );
 
     Ok(())
@@ -263,97 +539,306 @@ fn install(id: Identifier, version: Option<String>, config: Config) -> anyhow::R
         return Ok(());
     }
 
-    let client = Client::new(config.registry_url, config.password);
+    let client = Client::new(config.registry_url, config.token, config.password);
 
-    let version = match id.version.or(version) {
-        Some(version) => version,
+    let requirement = id.version.or(version);
+
+    let version = match &requirement {
+        Some(requirement) => {
+            let Some(version) = resolve(&client, &name, requirement, &triple)? else {
+                bail!("no versions of {name} matching {requirement} are available for {triple}");
+            };
+            if version != *requirement {
+                info!("resolved {name}@{requirement} to version {version}");
+            }
+            version
+        }
         None => {
-            let input = GetInfoInput {
-                name: name.clone(),
-                triple: triple.clone(),
+            let index = index::load(&client, &name)?;
+            let Some(latest) = index.latest(&triple) else {
+                bail!("no versions of {name} are available for {triple}");
             };
+            latest.version.clone()
+        }
+    };
+
+    let cached = fetch(&client, &name, &version, &triple)?;
+
+    check_signature(
+        &config.trusted_keys,
+        config.require_signatures,
+        &triple,
+        &cached.output,
+    )?;
+
+    // tags are remembered as the channel that upgrades follow and ranges are
+    // remembered so that upgrades stay within them, while exact versions are
+    // not so that upgrades move on to the latest version
+    let (requirement, channel) = match requirement {
+        Some(tag) if validate_tag(&tag).is_ok() => (None, Some(tag)),
+        requirement => (
+            requirement
+                .filter(|requirement| VersionReq::parse(requirement).is_ok_and(|r| !r.is_exact())),
+            None,
+        ),
+    };
 
-            let mut package_info = client
-                .get_info(input)
-                .context("'get_info' request failed")?;
+    deploy(name, version, requirement, channel, &cached)
+}
 
-            package_info
-                .versions
-                .pop()
-                .expect("at least one version should exist")
+/// Resolves a version requirement to a version of a package.
+///
+/// Requirements are resolved by the registry. If it cannot be reached, they
+/// are resolved against the local copy of the package index instead. Returns
+/// `None` if no available version satisfies the requirement.
+fn resolve(
+    client: &Client,
+    name: &str,
+    requirement: &str,
+    triple: &Triple,
+) -> anyhow::Result<Option<String>> {
+    let input = ResolveInput {
+        name: name.to_owned(),
+        requirement: requirement.to_owned(),
+        triple: triple.clone(),
+    };
+
+    match client.resolve(input) {
+        Ok(output) => Ok(Some(output.version)),
+        Err(client::Error::Specific(ResolveError::VersionNotFound)) => Ok(None),
+        Err(client::Error::Transport(e)) => {
+            debug!("{e:?}");
+            let index = index::load(client, name)?;
+            let entry = index.resolve(requirement, triple)?;
+            Ok(entry.map(|entry| entry.version.clone()))
         }
+        Err(e) => Err(e).context("'resolve' request failed"),
+    }
+}
+
+/// Installs a fetched package into the armory home.
+///
+/// Binaries are installed to `bin/{name}` and archives are unpacked into the
+/// armory home. Every installed file is recorded in the install manifest, and
+/// files left over from a previously installed version are removed. Files that
+/// belong to another package are never overwritten.
+fn deploy(
+    name: String,
+    version: String,
+    requirement: Option<String>,
+    channel: Option<String>,
+    cached: &cache::Cached,
+) -> anyhow::Result<()> {
+    let armory_home = dirs::armory_home();
+
+    let mut manifest = InstallManifest::load_or_create().context("failed to load manifest")?;
+
+    let files = match cached.output.format {
+        Format::Binary => vec![install_manifest::binary_path(&name)],
+        Format::Archive => archive::files(&cached.path).context("package archive is invalid")?,
     };
 
-    let content = match cache::get(&name, &version).context("failed to read cache")? {
-        // Use cached data if available
-        Some(cached) => {
-            info!("found package in cache");
-            cached
+    for file in &files {
+        if let Some(owner) = manifest.owner(file).filter(|owner| owner.name != name) {
+            bail!(
+                "{} is already installed by package {}",
+                file.display(),
+                owner.name
+            );
         }
+    }
 
-        // Otherwise fetch from registry
-        None => {
-            let input = GetInput {
-                name: name.clone(),
-                version: Some(version.clone()),
-                triple,
-            };
+    match cached.output.format {
+        Format::Binary => {
+            let artifact_path = armory_home.join(&files[0]);
+            fs::create_dir_all(armory_home.join("bin"))
+                .context("failed to create bin directory")?;
 
-            let output = client.get(input).context("'get' request failed")?;
+            remove_existing(&artifact_path)?;
 
-            let content = BASE64_STANDARD
-                .decode(output.content)
-                .context("package content is malformed")?;
+            fs::copy(&cached.path, &artifact_path).context("failed to store package in bin")?;
 
-            let cache_path = cache::put(&output.name, &output.version, &content)
-                .context("failed to cache package")?;
+            #[cfg(unix)]
+            {
+                use std::os::unix::fs::PermissionsExt;
+                fs::set_permissions(&artifact_path, fs::Permissions::from_mode(0o700))
+                    .context("failed to set binary permissions")?;
+            }
 
-            info!("cached package at {}", cache_path.display());
+            info!("installed binary to {}", artifact_path.display());
+        }
+        Format::Archive => {
+            archive::unpack(&cached.path, &armory_home).context("failed to unpack package")?;
+            for file in &files {
+                info!("installed {}", armory_home.join(file).display());
+            }
+        }
+    }
 
-            content
+    if let Some(previous) = manifest.package(&name) {
+        for file in previous.installed_files() {
+            if !files.contains(&file) {
+                remove_file(&armory_home.join(file))?;
+            }
         }
-    };
+    }
 
-    let bin = dirs::armory_home().join("bin");
-    fs::create_dir_all(&bin).context("failed to create bin directory")?;
+    manifest.add_package(name, version, requirement, channel, files);
+    manifest.save().context("failed to update manifest")?;
 
-    #[cfg(unix)]
-    let artifact_path = bin.join(&format!("{}", name));
+    Ok(())
+}
 
-    #[cfg(windows)]
-    let artifact_path = bin.join(&format!("{}.exe", name));
+/// Moves an existing file out of the way so that it can be replaced.
+///
+/// Running binaries cannot be deleted on Windows, so they are renamed instead.
+fn remove_existing(path: &Path) -> anyhow::Result<()> {
+    if !path.exists() {
+        return Ok(());
+    }
 
-    if artifact_path.exists() {
-        #[cfg(unix)]
-        fs::remove_file(&artifact_path).context("failed to remove existing package")?;
+    #[cfg(unix)]
+    fs::remove_file(path).context("failed to remove existing file")?;
 
-        #[cfg(windows)]
+    #[cfg(windows)]
+    {
+        let file_name = path.file_name().expect("path should name a file");
         fs::rename(
-            &artifact_path,
-            artifact_path.with_file_name(&format!("old_{}", name)),
+            path,
+            path.with_file_name(format!("old_{}", file_name.to_string_lossy())),
         )
-        .context("failed to remove existing package")?;
+        .context("failed to remove existing file")?;
+    }
+
+    info!("deleted existing file at {}", path.display());
 
-        info!("deleted existing binary at {}", artifact_path.display());
+    Ok(())
+}
+
+/// Deletes an installed file.
+///
+/// Files that have already been deleted are ignored.
+fn remove_file(path: &Path) -> anyhow::Result<()> {
+    match fs::remove_file(path) {
+        Ok(()) => info!("deleted {}", path.display()),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+        Err(e) => {
+            return Err(e).with_context(|| format!("failed to delete {}", path.display()));
+        }
     }
 
-    fs::write(&artifact_path, &content).context("failed to store package in bin")?;
+    Ok(())
+}
+
+/// Fetches a package from the cache or, failing that, the registry.
+///
+/// Packages fetched from the registry are streamed into the cache. The content
+/// is verified against the checksum reported by the registry on download and
+/// against the recorded checksum when read from the cache.
+fn fetch(
+    client: &Client,
+    name: &str,
+    version: &str,
+    triple: &Triple,
+) -> anyhow::Result<cache::Cached> {
+    let cached = cache::get(name, version).context("failed to read cache")?;
+
+    let input = GetInput {
+        name: name.to_owned(),
+        version: Some(version.to_owned()),
+        triple: triple.clone(),
+    };
+
+    // cached packages are revalidated rather than trusted outright, so that
+    // changes such as a newly attached signature are picked up without
+    // downloading unchanged content again
+    let etag = cached.as_ref().and_then(|cached| cached.etag.as_deref());
 
-    #[cfg(unix)]
-    {
-        use std::os::unix::fs::PermissionsExt;
-        fs::set_permissions(&artifact_path, fs::Permissions::from_mode(0o700))
-            .context("failed to set binary permissions")?;
+    // a download that was interrupted before is picked up where it left off
+    let resume = match cached {
+        Some(_) => None,
+        None => cache::resume(name, version),
+    };
+    if let Some(resume) = &resume {
+        info!("resuming download from byte {}", resume.offset);
     }
 
-    info!("installed binary to {}", artifact_path.display());
+    let cached = match client.get(input, etag, resume.as_ref()) {
+        Ok(Fetched::NotModified) => {
+            info!("found package in cache");
+            cached.expect("package should be cached if it was not modified")
+        }
+        Ok(Fetched::Modified {
+            value: download,
+            etag,
+        }) => {
+            let cached = cache::put(download, etag).context("failed to cache package")?;
+            info!("cached package at {}", cached.path.display());
+            cached
+        }
+        Err(client::Error::Transport(e)) if cached.is_some() => {
+            warn!("failed to reach registry; using cached package");
+            debug!("{e:?}");
+            cached.expect("package should be cached")
+        }
+        Err(e) => return Err(e).context("'get' request failed"),
+    };
 
-    InstallManifest::load_or_create()
-        .and_then(|mut manifest| {
-            manifest.add_package(name, version);
-            manifest.save()
-        })
-        .context("failed to update manifest")?;
+    if cached.output.yanked {
+        warn!("version {version} of {name} has been yanked");
+    }
+
+    Ok(cached)
+}
+
+/// Checks the publisher signature of a package against the trusted keys.
+///
+/// Invalid signatures are always rejected. Packages that are unsigned or signed
+/// by an untrusted key are rejected if signatures are required and installed
+/// otherwise.
+fn check_signature(
+    trusted_keys: &[String],
+    require_signatures: bool,
+    triple: &Triple,
+    output: &GetOutput,
+) -> anyhow::Result<()> {
+    let GetOutput {
+        name,
+        version,
+        checksum,
+        signature,
+        ..
+    } = output;
+
+    let Some(signature) = signature else {
+        if require_signatures {
+            bail!("refusing to install {name}-{version}: package is not signed and signatures are required");
+        }
+        debug!("package is not signed");
+        return Ok(());
+    };
+
+    signing::verify(signature, name, version, triple, checksum).with_context(|| {
+        format!("refusing to install {name}-{version}: signature verification failed")
+    })?;
+
+    let trusted = trusted_keys
+        .iter()
+        .any(|key| key.eq_ignore_ascii_case(&signature.public_key));
+
+    if trusted {
+        info!("verified signature from {}", signature.public_key);
+    } else if require_signatures {
+        bail!(
+            "refusing to install {name}-{version}: package is signed by untrusted key {}",
+            signature.public_key
+        );
+    } else {
+        warn!(
+            "package is signed by untrusted key {}; add it to trusted_keys to trust it",
+            signature.public_key
+        );
+    }
 
     Ok(())
 }
@@ -362,158 +847,330 @@ fn install(id: Identifier, version: Option<String>, config: Config) -> anyhow::R
 fn upgrade(config: Config) -> anyhow::Result<()> {
     let manifest = InstallManifest::load_or_create().context("failed to load manifest")?;
 
-    let client = Client::new(config.registry_url, config.password);
+    let client = Client::new(config.registry_url, config.token, config.password);
     let triple = target::triple()?;
     for package in manifest.packages() {
         let current_version = package.version.clone();
 
-        let get_info_input = GetInfoInput {
-            triple: triple.clone(),
-            name: package.name.clone(),
-        };
+        let latest_version = match package.channel.as_ref().or(package.requirement.as_ref()) {
+            Some(requirement) => {
+                let resolved = resolve(&client, &package.name, requirement, &triple)
+                    .with_context(|| format!("failed to resolve version of {}", package.name))?;
+                let Some(version) = resolved else {
+                    warn!(
+                        "no versions of {} matching {requirement} are available for {triple}",
+                        package.name
+                    );
+                    continue;
+                };
+                version
+            }
+            None => {
+                let index = index::load(&client, &package.name).with_context(|| {
+                    format!("failed to fetch index of package {}", package.name)
+                })?;
 
-        let mut package = client
-            .get_info(get_info_input)
-            .with_context(|| format!("failed to fetch info for package {}", package.name))?;
+                let Some(latest) = index.latest(&triple) else {
+                    warn!("no versions of {} are available for {triple}", package.name);
+                    continue;
+                };
 
-        let latest_version = package
-            .versions
-            .pop()
-            .expect("should be at least one version");
+                latest.version.clone()
+            }
+        };
 
-        // If the latest version is already installed, skip reinstall
-        if matches!(
-            sort_versions(&current_version, &latest_version),
-            Ordering::Equal | Ordering::Greater
-        ) {
+        // If the latest version is already installed, skip reinstall. Channels
+        // are followed wherever their tag points, even to a lower version.
+        let up_to_date = match package.channel {
+            Some(_) => current_version == latest_version,
+            None => matches!(
+                Version::parse(&current_version).precedence(&Version::parse(&latest_version)),
+                Ordering::Equal | Ordering::Greater
+            ),
+        };
+
+        if up_to_date {
             info!("package up to date: {} ({})", package.name, latest_version);
             continue;
         }
 
+        if package.pinned {
+            info!(
+                "held back pinned package: {} ({} -> {})",
+                package.name, current_version, latest_version
+            );
+            continue;
+        }
+
         info!(
             "upgrading package: {} ({} -> {})",
             package.name, current_version, latest_version
         );
 
         // Otherwise, install the latest version
-        let name = package.name;
+        let name = package.name.clone();
         let version = latest_version;
-        let content = match cache::get(&name, &version).context("failed to read cache")? {
-            // Use cached data if available
-            Some(cached) => {
-                info!("found package in cache");
-                cached
-            }
+        let cached = fetch(&client, &name, &version, &triple)?;
+
+        check_signature(
+            &config.trusted_keys,
+            config.require_signatures,
+            &triple,
+            &cached.output,
+        )?;
+
+        deploy(
+            name,
+            version,
+            package.requirement.clone(),
+            package.channel.clone(),
+            &cached,
+        )?;
+    }
 
-            // Otherwise fetch from registry
-            None => {
-                let input = GetInput {
-                    name: name.clone(),
-                    version: Some(version.clone()),
-                    triple: triple.clone(),
-                };
+    Ok(())
+}
 
-                let output = client.get(input).context("'get' request failed")?;
+/// List available packages.
+fn list(config: Config, installed: bool) -> anyhow::Result<()> {
+    if installed {
+        let manifest = InstallManifest::load_or_create().context("failed to load manifest")?;
+        println!("installed packages:");
+        for package in manifest.packages() {
+            let commands = package.commands();
+            let pinned = if package.pinned { " [pinned]" } else { "" };
+            match commands.as_slice() {
+                [command] if *command == package.name => {
+                    println!(
+                        "    {0: <20} {1: <10}{pinned}",
+                        package.name, package.version
+                    )
+                }
+                _ => println!(
+                    "    {0: <20} {1: <10} ({2}){pinned}",
+                    package.name,
+                    package.version,
+                    commands.join(", ")
+                ),
+            }
+        }
+    } else {
+        let triple = target::triple()?;
+        let client = Client::new(config.registry_url, config.token, config.password);
+        let mut packages = Vec::new();
+        let mut start_after = None;
+        loop {
+            let input = ListInput {
+                triple: triple.clone(),
+                start_after,
+                limit: None,
+            };
 
-                let content = BASE64_STANDARD
-                    .decode(output.content)
-                    .context("package content is malformed")?;
+            let output = match client.list(input) {
+                Ok(output) => output,
+                Err(client::Error::Transport(e)) => {
+                    warn!("failed to reach registry; showing cached packages only");
+                    debug!("{e:?}");
+                    packages = cached_summaries(&triple)?
+                        .into_iter()
+                        .map(|(summary, _)| summary)
+                        .collect();
+                    break;
+                }
+                Err(e) => return Err(e).context("'list' request failed"),
+            };
 
-                let cache_path = cache::put(&output.name, &output.version, &content)
-                    .context("failed to cache package")?;
+            packages.extend(output.packages);
+
+            start_after = match output.next {
+                Some(next) => Some(next),
+                None => break,
+            };
+        }
 
-                info!("cached package at {}", cache_path.display());
+        println!("available packages:");
+        for package in packages {
+            println!(
+                "    {0: <20} {1: <10} {2}",
+                package.name,
+                package.version,
+                package.description.unwrap_or_default()
+            );
+        }
+    }
 
-                content
-            }
-        };
+    Ok(())
+}
 
-        let bin = dirs::armory_home().join("bin");
-        fs::create_dir_all(&bin).context("failed to create bin directory")?;
+/// Show information about a package.
+fn package_info(config: Config, name: String) -> anyhow::Result<()> {
+    let client = Client::new(config.registry_url, config.token, config.password);
+    let index = index::load(&client, &name)?;
 
-        #[cfg(unix)]
-        let artifact_path = bin.join(&format!("{}", name));
+    let Some(last) = index.entries.last() else {
+        bail!("package {name} has no versions");
+    };
 
-        #[cfg(windows)]
-        let artifact_path = bin.join(&format!("{}.exe", name));
+    let latest = index.entries.iter().rev().find(|entry| !entry.yanked);
+    let metadata = &latest.unwrap_or(last).metadata;
 
-        if artifact_path.exists() {
-            #[cfg(unix)]
-            fs::remove_file(&artifact_path).context("failed to remove existing package")?;
+    match latest {
+        Some(latest) => println!("{name} {}", latest.version),
+        None => println!("{name} (all versions yanked)"),
+    }
 
-            #[cfg(windows)]
-            fs::rename(
-                &artifact_path,
-                artifact_path.with_file_name(&format!("old_{}", name)),
-            )
-            .context("failed to remove existing package")?;
+    if let Some(description) = &metadata.description {
+        println!("    {description}");
+    }
 
-            info!("deleted existing binary at {}", artifact_path.display());
+    let fields = [
+        ("homepage", metadata.homepage.clone()),
+        ("repository", metadata.repository.clone()),
+        ("license", metadata.license.clone()),
+        ("authors", Some(metadata.authors.join(", "))),
+        ("keywords", Some(metadata.keywords.join(", "))),
+    ];
+
+    for (field, value) in fields {
+        if let Some(value) = value.filter(|v| !v.is_empty()) {
+            println!("    {0: <12} {1}", format!("{field}:"), value);
         }
+    }
 
-        fs::write(&artifact_path, &content).context("failed to store package in bin")?;
+    let current = target::triple()?;
 
-        #[cfg(unix)]
-        {
-            use std::os::unix::fs::PermissionsExt;
-            fs::set_permissions(&artifact_path, fs::Permissions::from_mode(0o700))
-                .context("failed to set binary permissions")?;
-        }
+    println!("versions:");
+    for release in index.entries.iter().rev() {
+        let tags = match release.tags.is_empty() {
+            true => String::new(),
+            false => format!(" [{}]", release.tags.join(", ")),
+        };
 
-        info!("installed binary to {}", artifact_path.display());
+        match release.yanked {
+            true => println!("    {}{tags} (yanked)", release.version),
+            false => println!("    {}{tags}", release.version),
+        }
 
-        InstallManifest::load_or_create()
-            .and_then(|mut manifest| {
-                manifest.add_package(name, version);
-                manifest.save()
-            })
-            .context("failed to update manifest")?;
+        for target in &release.targets {
+            let marker = if target.triple == current { "*" } else { " " };
+            let published = match target.published_at {
+                Some(secs) => {
+                    humantime::format_rfc3339_seconds(UNIX_EPOCH + Duration::from_secs(secs))
+                        .to_string()
+                }
+                None => String::from("-"),
+            };
+            let compressed = match target.compressed_size {
+                Some(size) => format_size(size),
+                None => String::from("-"),
+            };
+            let signed = if target.signed { "signed" } else { "unsigned" };
+            println!(
+                "      {marker} {0: <16} {1: >10} {2: >10}  {3: <20}  {4}  {5}",
+                target.triple.to_string(),
+                format_size(target.size),
+                compressed,
+                published,
+                target.checksum,
+                signed
+            );
+        }
     }
 
     Ok(())
 }
 
-/// List available packages.
-fn list(config: Config, installed: bool) -> anyhow::Result<()> {
-    if installed {
-        let manifest = InstallManifest::load_or_create().context("failed to load manifest")?;
-        println!("installed packages:");
-        for package in manifest.packages() {
-            println!("    {0: <20} {1: <10}", package.name, package.version)
+/// Formats a size in bytes for display.
+fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+
+    if bytes < 1024 {
+        return format!("{bytes} B");
+    }
+
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = UNITS[0];
+    for next in &UNITS[1..] {
+        if size < 1024.0 {
+            break;
         }
-    } else {
-        let triple = target::triple()?;
-        let input = ListInput {
-            triple: triple.clone(),
-        };
-        let client = Client::new(config.registry_url, config.password);
-        let output = client.list(input).context("'list' request failed")?;
-        let mut buffer = String::new();
-        for package in output.packages {
-            let get_info_input = GetInfoInput {
-                triple: triple.clone(),
-                name: package.clone(),
-            };
+        size /= 1024.0;
+        unit = next;
+    }
 
-            let mut package = client
-                .get_info(get_info_input)
-                .with_context(|| format!("failed to fetch info for package {package}"))?;
+    format!("{size:.1} {unit}")
+}
 
-            let latest_version = package
-                .versions
-                .pop()
-                .expect("should be at least one version");
+/// Search for packages.
+fn search(config: Config, query: String) -> anyhow::Result<()> {
+    let input = SearchInput {
+        query: query.clone(),
+        triple: target::triple()?,
+    };
 
-            buffer += &format!("    {0: <20} {1: <10}\n", package.name, latest_version);
+    let client = Client::new(config.registry_url, config.token, config.password);
+    let results = match client.search(input) {
+        Ok(output) => output.results,
+        Err(client::Error::Transport(e)) => {
+            warn!("failed to reach registry; searching cached packages only");
+            debug!("{e:?}");
+            let mut matches: Vec<_> = cached_summaries(&target::triple()?)?
+                .into_iter()
+                .filter_map(|(summary, metadata)| {
+                    let score = utils::search::score(
+                        &query,
+                        &summary.name,
+                        metadata.description.as_deref(),
+                        &metadata.keywords,
+                    )?;
+                    Some((score, summary))
+                })
+                .collect();
+            matches.sort_by(|(a, _), (b, _)| b.cmp(a));
+            matches.into_iter().map(|(_, summary)| summary).collect()
         }
+        Err(e) => return Err(e).context("'search' request failed"),
+    };
 
-        println!("available packages:");
-        print!("{buffer}");
+    if results.is_empty() {
+        info!("no packages match '{query}'");
+        return Ok(());
+    }
+
+    println!("matching packages:");
+    for result in results {
+        println!(
+            "    {0: <20} {1: <10} {2}",
+            result.name,
+            result.version,
+            result.description.unwrap_or_default()
+        );
     }
 
     Ok(())
 }
 
+/// Summarizes the packages in the local index cache that are available for a
+/// triple.
+///
+/// Returns each summary along with the metadata of the latest version.
+fn cached_summaries(triple: &Triple) -> anyhow::Result<Vec<(PackageSummary, Metadata)>> {
+    let summaries = index::cached()?
+        .into_iter()
+        .filter_map(|index| {
+            let latest = index.latest(triple)?;
+            let summary = PackageSummary {
+                name: index.name.clone(),
+                version: latest.version.clone(),
+                description: latest.metadata.description.clone(),
+            };
+            Some((summary, latest.metadata.clone()))
+        })
+        .collect();
+
+    Ok(summaries)
+}
+
 /// Uninstall a package.
 fn uninstall(name: String, interactive: bool) -> anyhow::Result<()> {
     let armory_home = dirs::armory_home();
@@ -548,44 +1205,107 @@ fn uninstall(name: String, interactive: bool) -> anyhow::Result<()> {
         return Ok(());
     }
 
-    let bin = armory_home.join("bin");
+    let mut manifest = InstallManifest::load_or_create().context("failed to load manifest")?;
 
-    #[cfg(unix)]
-    let artifact_path = bin.join(&name);
+    // binaries installed without being recorded in the manifest are still
+    // removed
+    let files = match manifest.package(&name) {
+        Some(package) => package.installed_files(),
+        None => {
+            let binary = install_manifest::binary_path(&name);
+            if !armory_home.join(&binary).is_file() {
+                error!("package '{name}' does not exist");
+                return Ok(());
+            }
+            vec![binary]
+        }
+    };
 
-    #[cfg(windows)]
-    let artifact_path = bin.join(&format!("{}.exe", name));
+    for file in files {
+        remove_file(&armory_home.join(file))?;
+    }
+
+    manifest.remove_package(&name);
+    manifest.save().context("failed to update manifest")?;
+
+    Ok(())
+}
+
+/// Pin an installed package so that upgrades skip it.
+fn pin(config: Config, name: String, version: Option<String>) -> anyhow::Result<()> {
+    if let Some(version) = version {
+        let id = Identifier {
+            name: name.clone(),
+            version: Some(version),
+        };
+        install(id, None, config)?;
+    }
+
+    let mut manifest = InstallManifest::load_or_create().context("failed to load manifest")?;
+
+    let Some(package) = manifest.package_mut(&name) else {
+        bail!("package {name} is not installed");
+    };
+
+    package.pinned = true;
+    info!("pinned {name} at {}", package.version);
+
+    manifest.save().context("failed to update manifest")?;
+
+    Ok(())
+}
 
-    if !artifact_path.is_file() {
-        error!("package '{name}' does not exist");
+/// Unpin a package so that upgrades include it again.
+fn unpin(name: String) -> anyhow::Result<()> {
+    let mut manifest = InstallManifest::load_or_create().context("failed to load manifest")?;
+
+    let Some(package) = manifest.package_mut(&name) else {
+        bail!("package {name} is not installed");
+    };
+
+    if !package.pinned {
+        info!("package {name} is not pinned");
         return Ok(());
     }
 
-    fs::remove_file(&artifact_path)
-        .with_context(|| format!("failed to delete {}", artifact_path.display()))?;
+    package.pinned = false;
+    info!("unpinned {name}");
 
-    info!("deleted binary at {}", artifact_path.display());
+    manifest.save().context("failed to update manifest")?;
 
-    InstallManifest::load_or_create()
-        .and_then(|mut manifest| {
-            manifest.remove_package(&name);
-            manifest.save()
-        })
-        .context("failed to update manifest")?;
+    Ok(())
+}
 
+/// Print a script that sets up a shell to use installed packages.
+fn env(shell: Option<Shell>) -> anyhow::Result<()> {
+    let shell = shell.unwrap_or_else(Shell::detect);
+    print!("{}", shell.env(&dirs::armory_home()));
     Ok(())
 }
 
 /// Set up registry credentials.
-fn login() -> anyhow::Result<()> {
+///
+/// Other settings in the config file are preserved.
+fn login(password: bool) -> anyhow::Result<()> {
     let config_file = dirs::armory_home().join("config.toml");
 
-    let password = Password::new()
-        .with_prompt("enter your password")
-        .interact()?;
+    let (key, prompt) = match password {
+        true => ("password", "enter your password"),
+        false => ("token", "enter your token"),
+    };
+
+    let secret = Password::new().with_prompt(prompt).interact()?;
+
+    let mut config = match fs::read_to_string(&config_file) {
+        Ok(content) => {
+            toml::from_str::<toml::Table>(&content).context("failed to parse config file")?
+        }
+        Err(_) => toml::Table::new(),
+    };
 
-    let config = HashMap::from([("password", password)]);
+    config.insert(key.to_owned(), toml::Value::String(secret));
 
+    fs::create_dir_all(dirs::armory_home()).context("failed to create armory home")?;
     fs::write(&config_file, toml::to_string_pretty(&config)?)
         .context("failed to save config file")?;
 
@@ -593,3 +1313,213 @@ fn login() -> anyhow::Result<()> {
 
     Ok(())
 }
+
+/// Yank a version of a package, or undo a previous yank.
+fn yank(config: Config, id: Identifier, undo: bool) -> anyhow::Result<()> {
+    let Some(version) = id.version else {
+        bail!("a version must be specified to yank a package");
+    };
+
+    let client = Client::new(config.registry_url, config.token, config.password);
+
+    let input = YankInput {
+        name: id.name.clone(),
+        version: version.clone(),
+        yanked: !undo,
+    };
+
+    client.yank(input).context("'yank' request failed")?;
+
+    match undo {
+        true => info!("unyanked {}@{version}", id.name),
+        false => info!("yanked {}@{version}", id.name),
+    }
+
+    Ok(())
+}
+
+/// Point a tag of a package at a version, or remove the tag.
+fn tag_version(config: Config, id: Identifier, tag: String, remove: bool) -> anyhow::Result<()> {
+    validate_tag(&tag)?;
+
+    let version = match (id.version, remove) {
+        (Some(version), false) => Some(version),
+        (None, true) => None,
+        (None, false) => bail!("a version must be specified to tag a package"),
+        (Some(_), true) => bail!("a version cannot be specified when removing a tag"),
+    };
+
+    let client = Client::new(config.registry_url, config.token, config.password);
+
+    let input = TagInput {
+        name: id.name.clone(),
+        tag: tag.clone(),
+        version: version.clone(),
+    };
+
+    let output = client.tag(input).context("'tag' request failed")?;
+
+    match (version, output.previous) {
+        (Some(version), Some(previous)) if previous != version => {
+            info!(
+                "moved tag {tag} of {} from {previous} to {version}",
+                id.name
+            )
+        }
+        (Some(version), _) => info!("tagged {}@{version} as {tag}", id.name),
+        (None, _) => info!("removed tag {tag} of {}", id.name),
+    }
+
+    Ok(())
+}
+
+/// Remove a package, a version of a package, or a single artifact from the
+/// registry.
+fn unpublish(
+    config: Config,
+    id: Identifier,
+    triple: Option<Triple>,
+    interactive: bool,
+) -> anyhow::Result<()> {
+    let subject = match (&id.version, &triple) {
+        (Some(version), Some(triple)) => format!("{}@{version} for {triple}", id.name),
+        (Some(version), None) => format!("{}@{version}", id.name),
+        (None, None) => format!("every version of {}", id.name),
+        (None, Some(_)) => bail!("a version must be specified to unpublish a single triple"),
+    };
+
+    let confirm = if interactive {
+        Confirm::new()
+            .with_prompt(format!("permanently remove {subject} from the registry?"))
+            .interact()?
+    } else {
+        true
+    };
+
+    if !confirm {
+        info!("unpublish aborted");
+        return Ok(());
+    }
+
+    let client = Client::new(config.registry_url, config.token, config.password);
+
+    let input = UnpublishInput {
+        name: id.name,
+        version: id.version,
+        triple,
+    };
+
+    client
+        .unpublish(input)
+        .context("'unpublish' request failed")?;
+
+    info!("unpublished {subject}");
+
+    Ok(())
+}
+
+/// Manage the owners of a package.
+fn owner(config: Config, command: OwnerCommand) -> anyhow::Result<()> {
+    let client = Client::new(config.registry_url, config.token, config.password);
+
+    match command {
+        OwnerCommand::Add { package, user } => {
+            let input = AddOwnerInput {
+                name: package.clone(),
+                user: user.clone(),
+            };
+            client
+                .add_owner(input)
+                .context("'add_owner' request failed")?;
+            info!("added {user} as an owner of {package}");
+        }
+        OwnerCommand::Remove { package, user } => {
+            let input = RemoveOwnerInput {
+                name: package.clone(),
+                user: user.clone(),
+            };
+            client
+                .remove_owner(input)
+                .context("'remove_owner' request failed")?;
+            info!("removed {user} as an owner of {package}");
+        }
+        OwnerCommand::List { package } => {
+            let output = client
+                .list_owners(ListOwnersInput { name: package })
+                .context("'list_owners' request failed")?;
+            println!("owners:");
+            for owner in output.owners {
+                println!("    {owner}");
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Manage API tokens.
+fn token(config: Config, command: TokenCommand) -> anyhow::Result<()> {
+    let client = Client::new(config.registry_url, config.token, config.password);
+
+    match command {
+        TokenCommand::Create { user, scopes } => {
+            let input = CreateTokenInput { user, scopes };
+            let output = client
+                .create_token(input)
+                .context("'create_token' request failed")?;
+            info!(
+                "created token {} for user {}; it will not be shown again",
+                output.info.id, output.info.user
+            );
+            println!("{}", output.token);
+        }
+        TokenCommand::List => {
+            let output = client
+                .list_tokens(ListTokensInput {})
+                .context("'list_tokens' request failed")?;
+            println!("tokens:");
+            for token in output.tokens {
+                let scopes = token
+                    .scopes
+                    .iter()
+                    .map(|scope| scope.to_string())
+                    .collect::<Vec<_>>()
+                    .join(",");
+                let created = humantime::format_rfc3339_seconds(
+                    UNIX_EPOCH + Duration::from_secs(token.created_at),
+                );
+                println!(
+                    "    {0: <10} {1: <20} {2: <20} {3}",
+                    token.id, token.user, scopes, created
+                );
+            }
+        }
+        TokenCommand::Revoke { id } => {
+            client
+                .revoke_token(RevokeTokenInput { id: id.clone() })
+                .context("'revoke_token' request failed")?;
+            info!("revoked token {id}");
+        }
+    }
+
+    Ok(())
+}
+
+/// Manage the signing key.
+fn key(command: KeyCommand) -> anyhow::Result<()> {
+    match command {
+        KeyCommand::Generate { force } => {
+            let (key, path) = signing::generate(force)?;
+            info!("signing key saved at {}", path.display());
+            println!("{}", hex::encode(key.verifying_key().to_bytes()));
+        }
+        KeyCommand::Show => {
+            let Some(key) = signing::load()? else {
+                bail!("no signing key found; run `armory key generate` to create one");
+            };
+            println!("{}", hex::encode(key.verifying_key().to_bytes()));
+        }
+    }
+
+    Ok(())
+}
@@ -1,8 +1,15 @@
-use std::{fs, path::PathBuf};
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+};
 
 use anyhow::{bail, Context};
-use model::Triple;
+use model::{Metadata, Triple};
 use serde::Deserialize;
+use utils::validation::{validate_name, validate_semver};
+
+use crate::shell::Shell;
 
 /// Contains information needed to publish a package.
 #[derive(Deserialize)]
@@ -18,17 +25,181 @@ pub struct Package {
     pub name: String,
     /// The version of the package.
     pub version: String,
+    /// A short description of what the package does.
+    pub description: Option<String>,
+    /// The URL of the homepage of the package.
+    pub homepage: Option<String>,
+    /// The URL of the source repository of the package.
+    pub repository: Option<String>,
+    /// The license of the package, as an SPDX expression.
+    pub license: Option<String>,
+    /// The authors of the package.
+    #[serde(default)]
+    pub authors: Vec<String>,
+    /// Keywords that describe the package.
+    #[serde(default)]
+    pub keywords: Vec<String>,
+    /// The manual pages of the package.
+    ///
+    /// The section of each page is taken from its extension, such as `.1`.
+    #[serde(default)]
+    pub man: Vec<PathBuf>,
+    /// The completion scripts of the package, keyed by shell.
+    #[serde(default)]
+    pub completions: BTreeMap<Shell, PathBuf>,
+}
+
+impl Package {
+    /// Returns the descriptive metadata of the package.
+    pub fn metadata(&self) -> Metadata {
+        Metadata {
+            description: self.description.clone(),
+            homepage: self.homepage.clone(),
+            repository: self.repository.clone(),
+            license: self.license.clone(),
+            authors: self.authors.clone(),
+            keywords: self.keywords.clone(),
+        }
+    }
+
+    /// Returns the artifact that a target publishes.
+    ///
+    /// A target that only sets `path` is published as a single binary.
+    /// Otherwise, the binaries and files of the target are published as an
+    /// archive. The manual pages and completion scripts of the package are
+    /// added to the archive of every target, so a target that sets `path` is
+    /// also published as an archive if the package has any.
+    pub fn artifact<'a>(&self, target: &'a Target) -> anyhow::Result<Artifact<'a>> {
+        let extras = self.extras()?;
+
+        let binaries = match (&target.path, target.binaries.is_empty()) {
+            (Some(path), true) if target.files.is_empty() && extras.is_empty() => {
+                return Ok(Artifact::Binary(path))
+            }
+            (Some(path), true) => vec![TargetBinary {
+                name: self.name.clone(),
+                path: path.clone(),
+            }],
+            (None, _) => target.binaries.clone(),
+            (Some(_), false) => bail!("target {} sets both path and binaries", target.triple),
+        };
+
+        if binaries.is_empty() && target.files.is_empty() {
+            bail!(
+                "target {} sets none of path, binaries, and files",
+                target.triple
+            );
+        }
+
+        let mut files = Vec::new();
+        for binary in binaries {
+            let name = &binary.name;
+            if name.is_empty() || name == "." || name == ".." || name.contains(['/', '\\']) {
+                bail!("{name:?} is not a valid command name");
+            }
+
+            let file_name = match target.triple.is_windows() {
+                true => format!("{name}.exe"),
+                false => name.clone(),
+            };
+
+            files.push(TargetFile {
+                source: binary.path,
+                dest: Path::new("bin").join(file_name),
+            });
+        }
+
+        files.extend(target.files.iter().cloned());
+        files.extend(extras);
+        Ok(Artifact::Archive(files))
+    }
+
+    /// Returns the manual pages and completion scripts of the package as files
+    /// to publish.
+    ///
+    /// Manual pages are installed to `share/man/man{section}` and completion
+    /// scripts to the directory that each shell loads them from.
+    fn extras(&self) -> anyhow::Result<Vec<TargetFile>> {
+        let mut files = Vec::new();
+
+        for page in &self.man {
+            let section = page
+                .extension()
+                .and_then(|ext| ext.to_str())
+                .and_then(|ext| ext.chars().next())
+                .filter(|section| section.is_ascii_digit());
+            let (Some(section), Some(file_name)) = (section, page.file_name()) else {
+                bail!(
+                    "man page {} must have a section extension such as .1",
+                    page.display()
+                );
+            };
+            files.push(TargetFile {
+                source: page.clone(),
+                dest: Path::new("share/man")
+                    .join(format!("man{section}"))
+                    .join(file_name),
+            });
+        }
+
+        for (shell, script) in &self.completions {
+            files.push(TargetFile {
+                source: script.clone(),
+                dest: shell.completions_path(&self.name),
+            });
+        }
+
+        Ok(files)
+    }
 }
 
 /// A package target.
+///
+/// A target publishes either a single binary or an archive of binaries and
+/// other files.
 #[derive(Deserialize)]
 pub struct Target {
     /// The platform that the package binary corresponds to.
     pub triple: Triple,
     /// The path to the package binary.
+    ///
+    /// The binary is installed as a command named after the package.
+    pub path: Option<PathBuf>,
+    /// The binaries to publish in an archive, for packages that provide more
+    /// than one command.
+    #[serde(default)]
+    pub binaries: Vec<TargetBinary>,
+    /// The files to publish in an archive.
+    #[serde(default)]
+    pub files: Vec<TargetFile>,
+}
+
+/// A binary published in an archive.
+#[derive(Deserialize, Clone)]
+pub struct TargetBinary {
+    /// The name of the command that the binary is installed as.
+    pub name: String,
+    /// The path to the binary.
     pub path: PathBuf,
 }
 
+/// A file published in an archive.
+#[derive(Deserialize, Clone)]
+pub struct TargetFile {
+    /// The path to the file.
+    pub source: PathBuf,
+    /// The path that the file is installed to, relative to the armory home.
+    pub dest: PathBuf,
+}
+
+/// The artifact that a target publishes.
+pub enum Artifact<'a> {
+    /// A single binary.
+    Binary(&'a Path),
+    /// An archive of files.
+    Archive(Vec<TargetFile>),
+}
+
 impl PackageManifest {
     /// Loads the package manifest from the current working directory.
     ///
@@ -39,7 +210,9 @@ impl PackageManifest {
             bail!("no package manifest found in current directory");
         }
         let content = fs::read_to_string(path).context("failed to read manifest")?;
-        let manifest = toml::from_str(&content).context("failed to parse manifest")?;
+        let manifest: Self = toml::from_str(&content).context("failed to parse manifest")?;
+        validate_name(&manifest.package.name)?;
+        validate_semver(&manifest.package.version)?;
         Ok(manifest)
     }
 }
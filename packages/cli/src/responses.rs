@@ -0,0 +1,58 @@
+use std::{fs, path::PathBuf};
+
+use anyhow::Context;
+use sha2::{Digest, Sha256};
+
+/// A response to a read request that was stored for revalidation.
+pub struct Stored {
+    /// The ETag of the response.
+    pub etag: String,
+    /// The body of the response.
+    pub body: Vec<u8>,
+}
+
+/// Returns the path to the stored response for a key.
+fn path(key: &str) -> PathBuf {
+    crate::dirs::armory_cache()
+        .join("responses")
+        .join(format!("{key}.json"))
+}
+
+/// Returns the path to the recorded ETag of the stored response for a key.
+fn etag_path(key: &str) -> PathBuf {
+    crate::dirs::armory_cache()
+        .join("responses")
+        .join(format!("{key}.etag"))
+}
+
+/// Returns the key that a response is stored under.
+///
+/// Responses vary by request and by credentials, so the key is a digest of
+/// everything that identifies the request.
+pub fn key(parts: &[&str]) -> String {
+    let mut hasher = Sha256::new();
+    for part in parts {
+        hasher.update(part.len().to_le_bytes());
+        hasher.update(part);
+    }
+    format!("{:x}", hasher.finalize())
+}
+
+/// Loads the stored response for a key.
+///
+/// Returns `None` if no response is stored.
+pub fn get(key: &str) -> Option<Stored> {
+    let etag = fs::read_to_string(etag_path(key)).ok()?;
+    let body = fs::read(path(key)).ok()?;
+    Some(Stored { etag, body })
+}
+
+/// Stores a response under a key.
+pub fn put(key: &str, etag: &str, body: &[u8]) -> anyhow::Result<()> {
+    let path = path(key);
+    fs::create_dir_all(path.parent().expect("path should have parent"))
+        .context("failed to create response cache dir")?;
+    fs::write(&path, body).context("failed to store response")?;
+    fs::write(etag_path(key), etag).context("failed to store response etag")?;
+    Ok(())
+}
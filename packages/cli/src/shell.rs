@@ -0,0 +1,120 @@
+use std::path::{Path, PathBuf};
+
+use clap::ValueEnum;
+use serde::Deserialize;
+
+/// A supported shell.
+#[derive(Deserialize, ValueEnum, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum Shell {
+    Bash,
+    Zsh,
+    Fish,
+    Powershell,
+}
+
+impl Shell {
+    /// Detects the shell of the current user.
+    ///
+    /// Uses the `SHELL` environment variable, falling back to PowerShell on
+    /// Windows and Bash elsewhere.
+    pub fn detect() -> Self {
+        let name = std::env::var("SHELL").ok().and_then(|shell| {
+            Path::new(&shell)
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().into_owned())
+        });
+
+        match name.as_deref() {
+            Some("zsh") => Shell::Zsh,
+            Some("fish") => Shell::Fish,
+            Some("pwsh" | "powershell") => Shell::Powershell,
+            Some(_) => Shell::Bash,
+            None if cfg!(windows) => Shell::Powershell,
+            None => Shell::Bash,
+        }
+    }
+
+    /// Returns the directory that completion scripts for the shell are
+    /// installed to, relative to the armory home.
+    pub fn completions_dir(&self) -> PathBuf {
+        let dir = match self {
+            Shell::Bash => "share/bash-completion/completions",
+            Shell::Zsh => "share/zsh/site-functions",
+            Shell::Fish => "share/fish/vendor_completions.d",
+            Shell::Powershell => "share/powershell/completions",
+        };
+        PathBuf::from(dir)
+    }
+
+    /// Returns the path that the completion script of a package is installed
+    /// to, relative to the armory home.
+    ///
+    /// The file name follows the convention of each shell.
+    pub fn completions_path(&self, name: &str) -> PathBuf {
+        let file_name = match self {
+            Shell::Bash => name.to_owned(),
+            Shell::Zsh => format!("_{name}"),
+            Shell::Fish => format!("{name}.fish"),
+            Shell::Powershell => format!("{name}.ps1"),
+        };
+        self.completions_dir().join(file_name)
+    }
+
+    /// Returns a script that sets up the shell to use packages installed in
+    /// the armory home.
+    ///
+    /// The script adds the binary directory to the path, the manual directory
+    /// to the manual path, and loads installed completion scripts.
+    pub fn env(&self, armory_home: &Path) -> String {
+        let bin = armory_home.join("bin");
+        let man = armory_home.join("share").join("man");
+        let completions = armory_home.join(self.completions_dir());
+        let (bin, man, completions) = (bin.display(), man.display(), completions.display());
+
+        match self {
+            Shell::Bash => format!(
+                concat!(
+                    "export PATH=\"{bin}:$PATH\"\n",
+                    "export MANPATH=\"{man}:$MANPATH\"\n",
+                    "for f in \"{completions}\"/*; do\n",
+                    "    [ -r \"$f\" ] && . \"$f\"\n",
+                    "done\n",
+                    "unset f\n",
+                ),
+                bin = bin,
+                man = man,
+                completions = completions,
+            ),
+            Shell::Zsh => format!(
+                concat!(
+                    "export PATH=\"{bin}:$PATH\"\n",
+                    "export MANPATH=\"{man}:$MANPATH\"\n",
+                    "fpath=(\"{completions}\" $fpath)\n",
+                ),
+                bin = bin,
+                man = man,
+                completions = completions,
+            ),
+            Shell::Fish => format!(
+                concat!(
+                    "set -gx PATH \"{bin}\" $PATH\n",
+                    "set -gx MANPATH \"{man}\" $MANPATH ''\n",
+                    "set -g fish_complete_path \"{completions}\" $fish_complete_path\n",
+                ),
+                bin = bin,
+                man = man,
+                completions = completions,
+            ),
+            Shell::Powershell => format!(
+                concat!(
+                    "$env:PATH = \"{bin}\" + [IO.Path]::PathSeparator + $env:PATH\n",
+                    "Get-ChildItem \"{completions}\" -Filter *.ps1 -ErrorAction SilentlyContinue |\n",
+                    "    ForEach-Object {{ . $_.FullName }}\n",
+                ),
+                bin = bin,
+                completions = completions,
+            ),
+        }
+    }
+}
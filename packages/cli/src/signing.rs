@@ -0,0 +1,216 @@
+use std::{
+    fs::{self, OpenOptions},
+    io::Write,
+    path::PathBuf,
+};
+
+use anyhow::{anyhow, bail, Context};
+use ed25519_dalek::{Signer, SigningKey, Verifier, VerifyingKey};
+use model::{Signature, Triple};
+use rand_core::OsRng;
+
+/// Returns the path to the signing key.
+///
+/// It is located at ~/.armory/signing.key.
+fn key_path() -> PathBuf {
+    crate::dirs::armory_home().join("signing.key")
+}
+
+/// Generates a new signing key and saves it to disk.
+///
+/// Returns an error if a key already exists and `force` is not set.
+pub fn generate(force: bool) -> anyhow::Result<(SigningKey, PathBuf)> {
+    let path = key_path();
+    if path.exists() && !force {
+        bail!(
+            "a signing key already exists at {}; use --force to replace it",
+            path.display()
+        );
+    }
+
+    let key = SigningKey::generate(&mut OsRng);
+
+    fs::create_dir_all(crate::dirs::armory_home()).context("failed to create armory home")?;
+
+    let mut options = OpenOptions::new();
+    options
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .create_new(!force);
+
+    // create the file readable only by the owner so that the key is never
+    // exposed, even briefly
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+
+    let mut file = options
+        .open(&path)
+        .context("failed to create signing key")?;
+
+    // the mode only applies to new files, so tighten the permissions of a key
+    // that is being replaced before writing to it
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        file.set_permissions(fs::Permissions::from_mode(0o600))
+            .context("failed to set signing key permissions")?;
+    }
+
+    file.write_all(hex::encode(key.to_bytes()).as_bytes())
+        .context("failed to save signing key")?;
+
+    Ok((key, path))
+}
+
+/// Loads the signing key from disk.
+///
+/// Returns `None` if no key has been generated.
+pub fn load() -> anyhow::Result<Option<SigningKey>> {
+    let path = key_path();
+    if !path.exists() {
+        return Ok(None);
+    }
+
+    let content = fs::read_to_string(&path).context("failed to read signing key")?;
+    let bytes = hex::decode(content.trim())
+        .ok()
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or_else(|| anyhow!("signing key at {} is malformed", path.display()))?;
+
+    Ok(Some(SigningKey::from_bytes(&bytes)))
+}
+
+/// Returns the message that is signed for an artifact.
+///
+/// The message binds the checksum of the artifact to the package, version, and
+/// triple it was published as, so a signature cannot be replayed for a
+/// different package.
+fn message(name: &str, version: &str, triple: &Triple, checksum: &str) -> Vec<u8> {
+    format!("armory-signature-v1\n{name}\n{version}\n{triple}\n{checksum}\n").into_bytes()
+}
+
+/// Signs an artifact.
+pub fn sign(
+    key: &SigningKey,
+    name: &str,
+    version: &str,
+    triple: &Triple,
+    checksum: &str,
+) -> Signature {
+    let signature = key.sign(&message(name, version, triple, checksum));
+    Signature {
+        public_key: hex::encode(key.verifying_key().to_bytes()),
+        signature: hex::encode(signature.to_bytes()),
+    }
+}
+
+/// Verifies the signature over an artifact.
+///
+/// This only checks that the signature is valid for the public key it names.
+/// Whether that key is trusted is up to the caller.
+pub fn verify(
+    signature: &Signature,
+    name: &str,
+    version: &str,
+    triple: &Triple,
+    checksum: &str,
+) -> anyhow::Result<()> {
+    let public_key: [u8; 32] = hex::decode(&signature.public_key)
+        .ok()
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or_else(|| anyhow!("public key is malformed"))?;
+    let public_key = VerifyingKey::from_bytes(&public_key).context("public key is invalid")?;
+
+    let bytes: [u8; 64] = hex::decode(&signature.signature)
+        .ok()
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or_else(|| anyhow!("signature is malformed"))?;
+
+    public_key
+        .verify(
+            &message(name, version, triple, checksum),
+            &ed25519_dalek::Signature::from_bytes(&bytes),
+        )
+        .context("signature does not match artifact")
+}
+
+#[cfg(test)]
+mod tests {
+    use ed25519_dalek::SigningKey;
+    use model::Triple;
+
+    use super::{sign, verify};
+
+    const CHECKSUM: &str = "9f86d081884c7d659a2feaa0c55ad015a3bf4f1b2b0b822cd15d6c15b0f00a08";
+
+    fn key() -> SigningKey {
+        SigningKey::from_bytes(&[7; 32])
+    }
+
+    #[test]
+    fn round_trip() {
+        let signature = sign(&key(), "ripgrep", "1.0.0", &Triple::X86_64Linux, CHECKSUM);
+        assert!(verify(
+            &signature,
+            "ripgrep",
+            "1.0.0",
+            &Triple::X86_64Linux,
+            CHECKSUM
+        )
+        .is_ok());
+    }
+
+    #[test]
+    fn tampered_checksum() {
+        let signature = sign(&key(), "ripgrep", "1.0.0", &Triple::X86_64Linux, CHECKSUM);
+        let tampered = CHECKSUM.replace('9', "8");
+        assert!(verify(
+            &signature,
+            "ripgrep",
+            "1.0.0",
+            &Triple::X86_64Linux,
+            &tampered
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn replayed_signature() {
+        let signature = sign(&key(), "ripgrep", "1.0.0", &Triple::X86_64Linux, CHECKSUM);
+        assert!(verify(&signature, "fd", "1.0.0", &Triple::X86_64Linux, CHECKSUM).is_err());
+        assert!(verify(
+            &signature,
+            "ripgrep",
+            "1.0.1",
+            &Triple::X86_64Linux,
+            CHECKSUM
+        )
+        .is_err());
+        assert!(verify(
+            &signature,
+            "ripgrep",
+            "1.0.0",
+            &Triple::Aarch64Linux,
+            CHECKSUM
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn malformed_signature() {
+        let mut signature = sign(&key(), "ripgrep", "1.0.0", &Triple::X86_64Linux, CHECKSUM);
+        signature.signature.truncate(10);
+        assert!(verify(
+            &signature,
+            "ripgrep",
+            "1.0.0",
+            &Triple::X86_64Linux,
+            CHECKSUM
+        )
+        .is_err());
+    }
+}
@@ -10,7 +10,7 @@ pub fn triple() -> anyhow::Result<Triple> {
         ("aarch64", "macos") => Triple::Aarch64Darwin,
         ("x86_64", "windows") => Triple::X86_64Windows,
         ("aarch64", "windows") => Triple::Aarch64Windows,
-        target @ _ => bail!("unrecognized target {target:?}"),
+        target => bail!("unrecognized target {target:?}"),
     };
     Ok(triple)
 }
@@ -13,17 +13,23 @@ pub struct ErrorInfo {
 /// General errors that are shared across operations.
 #[derive(Error, Debug)]
 pub enum GeneralError {
-    #[error("no password was provided")]
-    PasswordMissing,
+    #[error("no token or password was provided")]
+    CredentialsMissing,
     #[error("password is invalid")]
     PasswordInvalid,
+    #[error("token is invalid or has been revoked")]
+    TokenInvalid,
+    #[error("credentials do not grant permission for this operation")]
+    PermissionDenied,
 }
 
 impl From<GeneralError> for ErrorInfo {
     fn from(value: GeneralError) -> Self {
         let code = match value {
-            GeneralError::PasswordMissing => "password_missing",
+            GeneralError::CredentialsMissing => "credentials_missing",
             GeneralError::PasswordInvalid => "password_invalid",
+            GeneralError::TokenInvalid => "token_invalid",
+            GeneralError::PermissionDenied => "permission_denied",
         };
 
         ErrorInfo {
@@ -38,15 +44,17 @@ impl TryFrom<ErrorInfo> for GeneralError {
     fn try_from(value: ErrorInfo) -> Result<Self, Self::Error> {
         let code = value.code.as_ref();
         match code {
-            "password_missing" => Ok(Self::PasswordMissing),
+            "credentials_missing" => Ok(Self::CredentialsMissing),
             "password_invalid" => Ok(Self::PasswordInvalid),
+            "token_invalid" => Ok(Self::TokenInvalid),
+            "permission_denied" => Ok(Self::PermissionDenied),
             _ => bail!("unrecognized error code: {code}"),
         }
     }
 }
 
 /// Target triple.
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 #[serde(rename_all = "snake_case")]
 pub enum Triple {
     X86_64Linux,
@@ -57,6 +65,13 @@ pub enum Triple {
     Aarch64Windows,
 }
 
+impl Triple {
+    /// Checks whether the triple is for Windows.
+    pub fn is_windows(&self) -> bool {
+        matches!(self, Triple::X86_64Windows | Triple::Aarch64Windows)
+    }
+}
+
 impl FromStr for Triple {
     type Err = anyhow::Error;
 
@@ -89,26 +104,158 @@ impl Display for Triple {
     }
 }
 
+/// The format of an artifact.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum Format {
+    /// A single binary.
+    #[default]
+    Binary,
+    /// A tar archive of files laid out under `bin/`, `share/`, `completions/`,
+    /// and `etc/`.
+    Archive,
+}
+
+impl Display for Format {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let text = match self {
+            Format::Binary => "binary",
+            Format::Archive => "archive",
+        };
+
+        write!(f, "{text}")
+    }
+}
+
+/// A permission granted to an API token.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum Scope {
+    /// Allows reading packages.
+    Read,
+    /// Allows publishing packages. Implies `Read`.
+    Publish,
+    /// Allows managing the registry, including tokens. Implies all other
+    /// scopes.
+    Admin,
+}
+
+impl Scope {
+    /// Checks whether this scope grants another scope.
+    pub fn grants(&self, other: Scope) -> bool {
+        match self {
+            Scope::Admin => true,
+            Scope::Publish => matches!(other, Scope::Publish | Scope::Read),
+            Scope::Read => matches!(other, Scope::Read),
+        }
+    }
+}
+
+impl FromStr for Scope {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let scope = match s {
+            "read" => Scope::Read,
+            "publish" => Scope::Publish,
+            "admin" => Scope::Admin,
+            _ => bail!("unrecognized scope {s}"),
+        };
+        Ok(scope)
+    }
+}
+
+impl Display for Scope {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let text = match self {
+            Scope::Read => "read",
+            Scope::Publish => "publish",
+            Scope::Admin => "admin",
+        };
+
+        write!(f, "{text}")
+    }
+}
+
+/// A publisher signature over an artifact.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Signature {
+    /// The ed25519 public key of the publisher, encoded as hex.
+    pub public_key: String,
+    /// The ed25519 signature, encoded as hex.
+    pub signature: String,
+}
+
+/// Descriptive metadata about a package.
+///
+/// Every field is optional.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct Metadata {
+    /// A short description of what the package does.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// The URL of the homepage of the package.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub homepage: Option<String>,
+    /// The URL of the source repository of the package.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub repository: Option<String>,
+    /// The license of the package, as an SPDX expression.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub license: Option<String>,
+    /// The authors of the package.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub authors: Vec<String>,
+    /// Keywords that describe the package.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub keywords: Vec<String>,
+}
+
 /// Input for the publish operation.
+///
+/// The artifact itself is not part of the input. It is streamed as the body of
+/// the request.
 #[derive(Serialize, Deserialize, Debug)]
 pub struct PublishInput {
     pub name: String,
     pub version: String,
     pub triple: Triple,
-    pub content: String,
+    /// The publisher signature over the artifact, if it is signed.
+    pub signature: Option<Signature>,
+    /// Descriptive metadata about the version.
+    #[serde(default)]
+    pub metadata: Metadata,
+    /// The format of the artifact.
+    #[serde(default)]
+    pub format: Format,
 }
 
 /// Output for the publish operation.
 #[derive(Serialize, Deserialize, Debug)]
-pub struct PublishOutput {}
+pub struct PublishOutput {
+    /// The size of the artifact in bytes.
+    #[serde(default)]
+    pub size: u64,
+    /// The size of the artifact in bytes once compressed with zstd.
+    #[serde(default)]
+    pub compressed_size: u64,
+}
 
 /// Errors for the publish operation.
 #[derive(Error, Debug)]
 pub enum PublishError {
-    #[error("content is not encoded correctly")]
-    InvalidEncoding,
+    #[error("input is missing or malformed")]
+    InvalidInput,
     #[error("version already exists")]
     VersionExists,
+    #[error("caller is not an owner of the package")]
+    NotOwner,
+    #[error("package name is invalid")]
+    InvalidName,
+    #[error("version is invalid")]
+    InvalidVersion,
+    #[error("artifact is too large")]
+    TooLarge,
     #[error("internal error")]
     InternalError,
 }
@@ -116,8 +263,12 @@ pub enum PublishError {
 impl From<PublishError> for ErrorInfo {
     fn from(value: PublishError) -> Self {
         let code = match value {
-            PublishError::InvalidEncoding => "invalid_encoding",
+            PublishError::InvalidInput => "invalid_input",
             PublishError::VersionExists => "version_exists",
+            PublishError::NotOwner => "not_owner",
+            PublishError::InvalidName => "invalid_name",
+            PublishError::InvalidVersion => "invalid_version",
+            PublishError::TooLarge => "too_large",
             PublishError::InternalError => "internal_error",
         };
 
@@ -133,8 +284,12 @@ impl TryFrom<ErrorInfo> for PublishError {
     fn try_from(value: ErrorInfo) -> Result<Self, Self::Error> {
         let code = value.code.as_ref();
         match code {
-            "invalid_encoding" => Ok(Self::InvalidEncoding),
+            "invalid_input" => Ok(Self::InvalidInput),
             "version_exists" => Ok(Self::VersionExists),
+            "not_owner" => Ok(Self::NotOwner),
+            "invalid_name" => Ok(Self::InvalidName),
+            "invalid_version" => Ok(Self::InvalidVersion),
+            "too_large" => Ok(Self::TooLarge),
             "internal_error" => Ok(Self::InternalError),
             _ => bail!("unrecognized error code: {code}"),
         }
@@ -150,11 +305,23 @@ pub struct GetInput {
 }
 
 /// Output for the get operation.
+///
+/// The artifact itself is not part of the output. It is streamed as the body
+/// of the response.
 #[derive(Serialize, Deserialize, Debug)]
 pub struct GetOutput {
     pub name: String,
     pub version: String,
-    pub content: String,
+    /// The SHA-256 digest of the artifact, encoded as lowercase hex.
+    pub checksum: String,
+    /// The publisher signature over the artifact, if it is signed.
+    pub signature: Option<Signature>,
+    /// Whether the version has been yanked.
+    #[serde(default)]
+    pub yanked: bool,
+    /// The format of the artifact.
+    #[serde(default)]
+    pub format: Format,
 }
 
 /// Errors for the get operation.
@@ -162,6 +329,10 @@ pub struct GetOutput {
 pub enum GetError {
     #[error("package does not exist")]
     PackageNotFound,
+    #[error("package name is invalid")]
+    InvalidName,
+    #[error("version is invalid")]
+    InvalidVersion,
     #[error("internal error")]
     InternalError,
 }
@@ -170,6 +341,8 @@ impl From<GetError> for ErrorInfo {
     fn from(value: GetError) -> Self {
         let code = match value {
             GetError::PackageNotFound => "package_not_found",
+            GetError::InvalidName => "invalid_name",
+            GetError::InvalidVersion => "invalid_version",
             GetError::InternalError => "internal_error",
         };
 
@@ -186,6 +359,288 @@ impl TryFrom<ErrorInfo> for GetError {
         let code = value.code.as_ref();
         match code {
             "package_not_found" => Ok(Self::PackageNotFound),
+            "invalid_name" => Ok(Self::InvalidName),
+            "invalid_version" => Ok(Self::InvalidVersion),
+            "internal_error" => Ok(Self::InternalError),
+            _ => bail!("unrecognized error code: {code}"),
+        }
+    }
+}
+
+/// Input for the resolve operation.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ResolveInput {
+    pub name: String,
+    /// The version requirement to resolve, such as `^1.2` or `>=2, <3`, or a
+    /// tag such as `beta`.
+    ///
+    /// A requirement that names a published version exactly resolves to that
+    /// version even if it has been yanked.
+    pub requirement: String,
+    pub triple: Triple,
+}
+
+/// Output for the resolve operation.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ResolveOutput {
+    pub name: String,
+    /// The highest available version that satisfies the requirement.
+    pub version: String,
+}
+
+/// Errors for the resolve operation.
+#[derive(Error, Debug)]
+pub enum ResolveError {
+    #[error("package does not exist")]
+    PackageNotFound,
+    #[error("version requirement is invalid")]
+    InvalidRequirement,
+    #[error("no available version satisfies the requirement")]
+    VersionNotFound,
+    #[error("package name is invalid")]
+    InvalidName,
+    #[error("internal error")]
+    InternalError,
+}
+
+impl From<ResolveError> for ErrorInfo {
+    fn from(value: ResolveError) -> Self {
+        let code = match value {
+            ResolveError::PackageNotFound => "package_not_found",
+            ResolveError::InvalidRequirement => "invalid_requirement",
+            ResolveError::VersionNotFound => "version_not_found",
+            ResolveError::InvalidName => "invalid_name",
+            ResolveError::InternalError => "internal_error",
+        };
+
+        ErrorInfo {
+            code: code.to_owned(),
+        }
+    }
+}
+
+impl TryFrom<ErrorInfo> for ResolveError {
+    type Error = anyhow::Error;
+
+    fn try_from(value: ErrorInfo) -> Result<Self, Self::Error> {
+        let code = value.code.as_ref();
+        match code {
+            "package_not_found" => Ok(Self::PackageNotFound),
+            "invalid_requirement" => Ok(Self::InvalidRequirement),
+            "version_not_found" => Ok(Self::VersionNotFound),
+            "invalid_name" => Ok(Self::InvalidName),
+            "internal_error" => Ok(Self::InternalError),
+            _ => bail!("unrecognized error code: {code}"),
+        }
+    }
+}
+
+/// Input for the yank operation.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct YankInput {
+    pub name: String,
+    pub version: String,
+    /// Whether to yank the version or undo a previous yank.
+    pub yanked: bool,
+}
+
+/// Output for the yank operation.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct YankOutput {}
+
+/// Errors for the yank operation.
+#[derive(Error, Debug)]
+pub enum YankError {
+    #[error("package does not exist")]
+    PackageNotFound,
+    #[error("version does not exist")]
+    VersionNotFound,
+    #[error("caller is not an owner of the package")]
+    NotOwner,
+    #[error("package name is invalid")]
+    InvalidName,
+    #[error("version is invalid")]
+    InvalidVersion,
+    #[error("internal error")]
+    InternalError,
+}
+
+impl From<YankError> for ErrorInfo {
+    fn from(value: YankError) -> Self {
+        let code = match value {
+            YankError::PackageNotFound => "package_not_found",
+            YankError::VersionNotFound => "version_not_found",
+            YankError::NotOwner => "not_owner",
+            YankError::InvalidName => "invalid_name",
+            YankError::InvalidVersion => "invalid_version",
+            YankError::InternalError => "internal_error",
+        };
+
+        ErrorInfo {
+            code: code.to_owned(),
+        }
+    }
+}
+
+impl TryFrom<ErrorInfo> for YankError {
+    type Error = anyhow::Error;
+
+    fn try_from(value: ErrorInfo) -> Result<Self, Self::Error> {
+        let code = value.code.as_ref();
+        match code {
+            "package_not_found" => Ok(Self::PackageNotFound),
+            "version_not_found" => Ok(Self::VersionNotFound),
+            "not_owner" => Ok(Self::NotOwner),
+            "invalid_name" => Ok(Self::InvalidName),
+            "invalid_version" => Ok(Self::InvalidVersion),
+            "internal_error" => Ok(Self::InternalError),
+            _ => bail!("unrecognized error code: {code}"),
+        }
+    }
+}
+
+/// Input for the tag operation.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct TagInput {
+    pub name: String,
+    pub tag: String,
+    /// The version to point the tag at.
+    ///
+    /// If not specified, the tag is removed.
+    pub version: Option<String>,
+}
+
+/// Output for the tag operation.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct TagOutput {
+    /// The version that the tag pointed to before, if any.
+    pub previous: Option<String>,
+}
+
+/// Errors for the tag operation.
+#[derive(Error, Debug)]
+pub enum TagError {
+    #[error("package does not exist")]
+    PackageNotFound,
+    #[error("version does not exist")]
+    VersionNotFound,
+    #[error("tag does not exist")]
+    TagNotFound,
+    #[error("caller is not an owner of the package")]
+    NotOwner,
+    #[error("package name is invalid")]
+    InvalidName,
+    #[error("version is invalid")]
+    InvalidVersion,
+    #[error("tag is invalid")]
+    InvalidTag,
+    #[error("internal error")]
+    InternalError,
+}
+
+impl From<TagError> for ErrorInfo {
+    fn from(value: TagError) -> Self {
+        let code = match value {
+            TagError::PackageNotFound => "package_not_found",
+            TagError::VersionNotFound => "version_not_found",
+            TagError::TagNotFound => "tag_not_found",
+            TagError::NotOwner => "not_owner",
+            TagError::InvalidName => "invalid_name",
+            TagError::InvalidVersion => "invalid_version",
+            TagError::InvalidTag => "invalid_tag",
+            TagError::InternalError => "internal_error",
+        };
+
+        ErrorInfo {
+            code: code.to_owned(),
+        }
+    }
+}
+
+impl TryFrom<ErrorInfo> for TagError {
+    type Error = anyhow::Error;
+
+    fn try_from(value: ErrorInfo) -> Result<Self, Self::Error> {
+        let code = value.code.as_ref();
+        match code {
+            "package_not_found" => Ok(Self::PackageNotFound),
+            "version_not_found" => Ok(Self::VersionNotFound),
+            "tag_not_found" => Ok(Self::TagNotFound),
+            "not_owner" => Ok(Self::NotOwner),
+            "invalid_name" => Ok(Self::InvalidName),
+            "invalid_version" => Ok(Self::InvalidVersion),
+            "invalid_tag" => Ok(Self::InvalidTag),
+            "internal_error" => Ok(Self::InternalError),
+            _ => bail!("unrecognized error code: {code}"),
+        }
+    }
+}
+
+/// Input for the unpublish operation.
+///
+/// Removes a single artifact if both a version and a triple are specified, every
+/// artifact of a version if only a version is specified, or the whole package
+/// otherwise.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct UnpublishInput {
+    pub name: String,
+    pub version: Option<String>,
+    pub triple: Option<Triple>,
+}
+
+/// Output for the unpublish operation.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct UnpublishOutput {}
+
+/// Errors for the unpublish operation.
+#[derive(Error, Debug)]
+pub enum UnpublishError {
+    #[error("a triple can only be specified along with a version")]
+    InvalidInput,
+    #[error("package does not exist")]
+    PackageNotFound,
+    #[error("version does not exist")]
+    VersionNotFound,
+    #[error("version is not available for triple")]
+    TargetNotFound,
+    #[error("package name is invalid")]
+    InvalidName,
+    #[error("version is invalid")]
+    InvalidVersion,
+    #[error("internal error")]
+    InternalError,
+}
+
+impl From<UnpublishError> for ErrorInfo {
+    fn from(value: UnpublishError) -> Self {
+        let code = match value {
+            UnpublishError::InvalidInput => "invalid_input",
+            UnpublishError::PackageNotFound => "package_not_found",
+            UnpublishError::VersionNotFound => "version_not_found",
+            UnpublishError::TargetNotFound => "target_not_found",
+            UnpublishError::InvalidName => "invalid_name",
+            UnpublishError::InvalidVersion => "invalid_version",
+            UnpublishError::InternalError => "internal_error",
+        };
+
+        ErrorInfo {
+            code: code.to_owned(),
+        }
+    }
+}
+
+impl TryFrom<ErrorInfo> for UnpublishError {
+    type Error = anyhow::Error;
+
+    fn try_from(value: ErrorInfo) -> Result<Self, Self::Error> {
+        let code = value.code.as_ref();
+        match code {
+            "invalid_input" => Ok(Self::InvalidInput),
+            "package_not_found" => Ok(Self::PackageNotFound),
+            "version_not_found" => Ok(Self::VersionNotFound),
+            "target_not_found" => Ok(Self::TargetNotFound),
+            "invalid_name" => Ok(Self::InvalidName),
+            "invalid_version" => Ok(Self::InvalidVersion),
             "internal_error" => Ok(Self::InternalError),
             _ => bail!("unrecognized error code: {code}"),
         }
@@ -196,14 +651,61 @@ impl TryFrom<ErrorInfo> for GetError {
 #[derive(Serialize, Deserialize, Debug)]
 pub struct GetInfoInput {
     pub name: String,
-    pub triple: Triple,
+    /// The triple to restrict the information to.
+    ///
+    /// If not specified, information about every triple is returned.
+    pub triple: Option<Triple>,
 }
 
 /// Output for the get_info operation.
 #[derive(Serialize, Deserialize, Debug)]
 pub struct GetInfoOutput {
     pub name: String,
+    /// The available versions of the package in ascending order.
+    ///
+    /// Yanked versions are not included.
     pub versions: Vec<String>,
+    /// Descriptive metadata about the latest version.
+    #[serde(default)]
+    pub metadata: Metadata,
+    /// Every published version of the package in ascending order, including
+    /// yanked versions.
+    #[serde(default)]
+    pub releases: Vec<ReleaseInfo>,
+}
+
+/// Information about a published version of a package.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ReleaseInfo {
+    pub version: String,
+    /// Whether the version has been yanked.
+    pub yanked: bool,
+    /// The artifacts published for the version.
+    pub targets: Vec<TargetInfo>,
+}
+
+/// Information about an artifact published for a specific triple.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TargetInfo {
+    pub triple: Triple,
+    /// The size of the artifact in bytes.
+    pub size: u64,
+    /// The size of the artifact in bytes once compressed with zstd.
+    ///
+    /// This is unknown for artifacts that are stored uncompressed.
+    #[serde(default)]
+    pub compressed_size: Option<u64>,
+    /// The SHA-256 digest of the artifact, encoded as lowercase hex.
+    pub checksum: String,
+    /// When the artifact was published, in seconds since the Unix epoch.
+    ///
+    /// This is unknown for artifacts published before it was recorded.
+    pub published_at: Option<u64>,
+    /// Whether the artifact carries a publisher signature.
+    pub signed: bool,
+    /// The format of the artifact.
+    #[serde(default)]
+    pub format: Format,
 }
 
 /// Errors for the get_info operation.
@@ -211,6 +713,8 @@ pub struct GetInfoOutput {
 pub enum GetInfoError {
     #[error("package does not exist")]
     PackageNotFound,
+    #[error("package name is invalid")]
+    InvalidName,
     #[error("internal error")]
     InternalError,
 }
@@ -219,6 +723,7 @@ impl From<GetInfoError> for ErrorInfo {
     fn from(value: GetInfoError) -> Self {
         let code = match value {
             GetInfoError::PackageNotFound => "package_not_found",
+            GetInfoError::InvalidName => "invalid_name",
             GetInfoError::InternalError => "internal_error",
         };
 
@@ -235,6 +740,68 @@ impl TryFrom<ErrorInfo> for GetInfoError {
         let code = value.code.as_ref();
         match code {
             "package_not_found" => Ok(Self::PackageNotFound),
+            "invalid_name" => Ok(Self::InvalidName),
+            "internal_error" => Ok(Self::InternalError),
+            _ => bail!("unrecognized error code: {code}"),
+        }
+    }
+}
+
+/// An entry in the index of a package.
+///
+/// The index of a package is a static document that describes every published
+/// version of the package, with one JSON-encoded entry per line in ascending
+/// version order. Clients can keep a local copy and refresh it with
+/// conditional requests.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct IndexEntry {
+    pub version: String,
+    /// Whether the version has been yanked.
+    #[serde(default)]
+    pub yanked: bool,
+    /// Descriptive metadata about the version.
+    #[serde(default)]
+    pub metadata: Metadata,
+    /// The artifacts published for the version.
+    pub targets: Vec<TargetInfo>,
+    /// The tags that point to the version.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+}
+
+/// Errors for the get_index operation.
+#[derive(Error, Debug)]
+pub enum GetIndexError {
+    #[error("package does not exist")]
+    PackageNotFound,
+    #[error("package name is invalid")]
+    InvalidName,
+    #[error("internal error")]
+    InternalError,
+}
+
+impl From<GetIndexError> for ErrorInfo {
+    fn from(value: GetIndexError) -> Self {
+        let code = match value {
+            GetIndexError::PackageNotFound => "package_not_found",
+            GetIndexError::InvalidName => "invalid_name",
+            GetIndexError::InternalError => "internal_error",
+        };
+
+        ErrorInfo {
+            code: code.to_owned(),
+        }
+    }
+}
+
+impl TryFrom<ErrorInfo> for GetIndexError {
+    type Error = anyhow::Error;
+
+    fn try_from(value: ErrorInfo) -> Result<Self, Self::Error> {
+        let code = value.code.as_ref();
+        match code {
+            "package_not_found" => Ok(Self::PackageNotFound),
+            "invalid_name" => Ok(Self::InvalidName),
             "internal_error" => Ok(Self::InternalError),
             _ => bail!("unrecognized error code: {code}"),
         }
@@ -245,12 +812,38 @@ impl TryFrom<ErrorInfo> for GetInfoError {
 #[derive(Serialize, Deserialize, Debug)]
 pub struct ListInput {
     pub triple: Triple,
+    /// Only list packages whose names sort after this one.
+    ///
+    /// Used to fetch the next page of results.
+    #[serde(default)]
+    pub start_after: Option<String>,
+    /// The maximum number of packages to list.
+    ///
+    /// The registry uses a default limit if not specified, and caps larger
+    /// limits.
+    #[serde(default)]
+    pub limit: Option<usize>,
 }
 
 /// Output for the list operation.
 #[derive(Serialize, Deserialize, Debug)]
 pub struct ListOutput {
-    pub packages: Vec<String>,
+    /// The packages that are available for the triple, ordered by name.
+    pub packages: Vec<PackageSummary>,
+    /// The value of `start_after` to fetch the next page of results with.
+    ///
+    /// There are no more results if this is not set.
+    pub next: Option<String>,
+}
+
+/// A summary of a package.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct PackageSummary {
+    pub name: String,
+    /// The latest version of the package.
+    pub version: String,
+    /// The description of the latest version of the package, if any.
+    pub description: Option<String>,
 }
 
 /// Errors for the list operation.
@@ -283,3 +876,387 @@ impl From<ListError> for ErrorInfo {
         }
     }
 }
+
+/// Input for the search operation.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SearchInput {
+    pub query: String,
+    pub triple: Triple,
+}
+
+/// Output for the search operation.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SearchOutput {
+    /// The matching packages, best matches first.
+    pub results: Vec<PackageSummary>,
+}
+
+/// Errors for the search operation.
+#[derive(Error, Debug)]
+pub enum SearchError {
+    #[error("internal error")]
+    InternalError,
+}
+
+impl TryFrom<ErrorInfo> for SearchError {
+    type Error = anyhow::Error;
+
+    fn try_from(value: ErrorInfo) -> Result<Self, Self::Error> {
+        let code = value.code.as_ref();
+        match code {
+            "internal_error" => Ok(Self::InternalError),
+            _ => bail!("unrecognized error code: {code}"),
+        }
+    }
+}
+
+impl From<SearchError> for ErrorInfo {
+    fn from(value: SearchError) -> Self {
+        let code = match value {
+            SearchError::InternalError => "internal_error",
+        };
+
+        ErrorInfo {
+            code: code.to_owned(),
+        }
+    }
+}
+
+/// Information about an API token.
+///
+/// This never includes the token itself, which is only revealed once, when it
+/// is created.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TokenInfo {
+    /// The identifier of the token.
+    pub id: String,
+    /// The user that the token belongs to.
+    pub user: String,
+    /// The scopes granted to the token.
+    pub scopes: Vec<Scope>,
+    /// When the token was created, in seconds since the Unix epoch.
+    pub created_at: u64,
+}
+
+/// Input for the create_token operation.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct CreateTokenInput {
+    pub user: String,
+    pub scopes: Vec<Scope>,
+}
+
+/// Output for the create_token operation.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct CreateTokenOutput {
+    pub info: TokenInfo,
+    /// The token itself.
+    pub token: String,
+}
+
+/// Errors for the create_token operation.
+#[derive(Error, Debug)]
+pub enum CreateTokenError {
+    #[error("at least one scope is required")]
+    ScopesMissing,
+    #[error("internal error")]
+    InternalError,
+}
+
+impl From<CreateTokenError> for ErrorInfo {
+    fn from(value: CreateTokenError) -> Self {
+        let code = match value {
+            CreateTokenError::ScopesMissing => "scopes_missing",
+            CreateTokenError::InternalError => "internal_error",
+        };
+
+        ErrorInfo {
+            code: code.to_owned(),
+        }
+    }
+}
+
+impl TryFrom<ErrorInfo> for CreateTokenError {
+    type Error = anyhow::Error;
+
+    fn try_from(value: ErrorInfo) -> Result<Self, Self::Error> {
+        let code = value.code.as_ref();
+        match code {
+            "scopes_missing" => Ok(Self::ScopesMissing),
+            "internal_error" => Ok(Self::InternalError),
+            _ => bail!("unrecognized error code: {code}"),
+        }
+    }
+}
+
+/// Input for the list_tokens operation.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ListTokensInput {}
+
+/// Output for the list_tokens operation.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ListTokensOutput {
+    pub tokens: Vec<TokenInfo>,
+}
+
+/// Errors for the list_tokens operation.
+#[derive(Error, Debug)]
+pub enum ListTokensError {
+    #[error("internal error")]
+    InternalError,
+}
+
+impl From<ListTokensError> for ErrorInfo {
+    fn from(value: ListTokensError) -> Self {
+        let code = match value {
+            ListTokensError::InternalError => "internal_error",
+        };
+
+        ErrorInfo {
+            code: code.to_owned(),
+        }
+    }
+}
+
+impl TryFrom<ErrorInfo> for ListTokensError {
+    type Error = anyhow::Error;
+
+    fn try_from(value: ErrorInfo) -> Result<Self, Self::Error> {
+        let code = value.code.as_ref();
+        match code {
+            "internal_error" => Ok(Self::InternalError),
+            _ => bail!("unrecognized error code: {code}"),
+        }
+    }
+}
+
+/// Input for the revoke_token operation.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct RevokeTokenInput {
+    pub id: String,
+}
+
+/// Output for the revoke_token operation.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct RevokeTokenOutput {}
+
+/// Errors for the revoke_token operation.
+#[derive(Error, Debug)]
+pub enum RevokeTokenError {
+    #[error("token does not exist")]
+    TokenNotFound,
+    #[error("internal error")]
+    InternalError,
+}
+
+impl From<RevokeTokenError> for ErrorInfo {
+    fn from(value: RevokeTokenError) -> Self {
+        let code = match value {
+            RevokeTokenError::TokenNotFound => "token_not_found",
+            RevokeTokenError::InternalError => "internal_error",
+        };
+
+        ErrorInfo {
+            code: code.to_owned(),
+        }
+    }
+}
+
+impl TryFrom<ErrorInfo> for RevokeTokenError {
+    type Error = anyhow::Error;
+
+    fn try_from(value: ErrorInfo) -> Result<Self, Self::Error> {
+        let code = value.code.as_ref();
+        match code {
+            "token_not_found" => Ok(Self::TokenNotFound),
+            "internal_error" => Ok(Self::InternalError),
+            _ => bail!("unrecognized error code: {code}"),
+        }
+    }
+}
+
+/// Input for the add_owner operation.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct AddOwnerInput {
+    pub name: String,
+    pub user: String,
+}
+
+/// Output for the add_owner operation.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct AddOwnerOutput {}
+
+/// Errors for the add_owner operation.
+#[derive(Error, Debug)]
+pub enum AddOwnerError {
+    #[error("package does not exist")]
+    PackageNotFound,
+    #[error("caller is not an owner of the package")]
+    NotOwner,
+    #[error("package name is invalid")]
+    InvalidName,
+    #[error("internal error")]
+    InternalError,
+}
+
+impl From<AddOwnerError> for ErrorInfo {
+    fn from(value: AddOwnerError) -> Self {
+        let code = match value {
+            AddOwnerError::PackageNotFound => "package_not_found",
+            AddOwnerError::NotOwner => "not_owner",
+            AddOwnerError::InvalidName => "invalid_name",
+            AddOwnerError::InternalError => "internal_error",
+        };
+
+        ErrorInfo {
+            code: code.to_owned(),
+        }
+    }
+}
+
+impl TryFrom<ErrorInfo> for AddOwnerError {
+    type Error = anyhow::Error;
+
+    fn try_from(value: ErrorInfo) -> Result<Self, Self::Error> {
+        let code = value.code.as_ref();
+        match code {
+            "package_not_found" => Ok(Self::PackageNotFound),
+            "not_owner" => Ok(Self::NotOwner),
+            "invalid_name" => Ok(Self::InvalidName),
+            "internal_error" => Ok(Self::InternalError),
+            _ => bail!("unrecognized error code: {code}"),
+        }
+    }
+}
+
+/// Input for the remove_owner operation.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct RemoveOwnerInput {
+    pub name: String,
+    pub user: String,
+}
+
+/// Output for the remove_owner operation.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct RemoveOwnerOutput {}
+
+/// Errors for the remove_owner operation.
+#[derive(Error, Debug)]
+pub enum RemoveOwnerError {
+    #[error("package does not exist")]
+    PackageNotFound,
+    #[error("caller is not an owner of the package")]
+    NotOwner,
+    #[error("user is not an owner of the package")]
+    OwnerNotFound,
+    #[error("cannot remove the last owner of a package")]
+    LastOwner,
+    #[error("package name is invalid")]
+    InvalidName,
+    #[error("internal error")]
+    InternalError,
+}
+
+impl From<RemoveOwnerError> for ErrorInfo {
+    fn from(value: RemoveOwnerError) -> Self {
+        let code = match value {
+            RemoveOwnerError::PackageNotFound => "package_not_found",
+            RemoveOwnerError::NotOwner => "not_owner",
+            RemoveOwnerError::OwnerNotFound => "owner_not_found",
+            RemoveOwnerError::LastOwner => "last_owner",
+            RemoveOwnerError::InvalidName => "invalid_name",
+            RemoveOwnerError::InternalError => "internal_error",
+        };
+
+        ErrorInfo {
+            code: code.to_owned(),
+        }
+    }
+}
+
+impl TryFrom<ErrorInfo> for RemoveOwnerError {
+    type Error = anyhow::Error;
+
+    fn try_from(value: ErrorInfo) -> Result<Self, Self::Error> {
+        let code = value.code.as_ref();
+        match code {
+            "package_not_found" => Ok(Self::PackageNotFound),
+            "not_owner" => Ok(Self::NotOwner),
+            "owner_not_found" => Ok(Self::OwnerNotFound),
+            "last_owner" => Ok(Self::LastOwner),
+            "invalid_name" => Ok(Self::InvalidName),
+            "internal_error" => Ok(Self::InternalError),
+            _ => bail!("unrecognized error code: {code}"),
+        }
+    }
+}
+
+/// Input for the list_owners operation.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ListOwnersInput {
+    pub name: String,
+}
+
+/// Output for the list_owners operation.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ListOwnersOutput {
+    pub owners: Vec<String>,
+}
+
+/// Errors for the list_owners operation.
+#[derive(Error, Debug)]
+pub enum ListOwnersError {
+    #[error("package does not exist")]
+    PackageNotFound,
+    #[error("package name is invalid")]
+    InvalidName,
+    #[error("internal error")]
+    InternalError,
+}
+
+impl From<ListOwnersError> for ErrorInfo {
+    fn from(value: ListOwnersError) -> Self {
+        let code = match value {
+            ListOwnersError::PackageNotFound => "package_not_found",
+            ListOwnersError::InvalidName => "invalid_name",
+            ListOwnersError::InternalError => "internal_error",
+        };
+
+        ErrorInfo {
+            code: code.to_owned(),
+        }
+    }
+}
+
+impl TryFrom<ErrorInfo> for ListOwnersError {
+    type Error = anyhow::Error;
+
+    fn try_from(value: ErrorInfo) -> Result<Self, Self::Error> {
+        let code = value.code.as_ref();
+        match code {
+            "package_not_found" => Ok(Self::PackageNotFound),
+            "invalid_name" => Ok(Self::InvalidName),
+            "internal_error" => Ok(Self::InternalError),
+            _ => bail!("unrecognized error code: {code}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Scope;
+
+    #[test]
+    fn scope_ordering() {
+        assert!(Scope::Admin.grants(Scope::Admin));
+        assert!(Scope::Admin.grants(Scope::Publish));
+        assert!(Scope::Admin.grants(Scope::Read));
+
+        assert!(!Scope::Publish.grants(Scope::Admin));
+        assert!(Scope::Publish.grants(Scope::Publish));
+        assert!(Scope::Publish.grants(Scope::Read));
+
+        assert!(!Scope::Read.grants(Scope::Admin));
+        assert!(!Scope::Read.grants(Scope::Publish));
+        assert!(Scope::Read.grants(Scope::Read));
+    }
+}
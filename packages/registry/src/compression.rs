@@ -0,0 +1,34 @@
+use std::io;
+
+use async_compression::tokio::bufread::ZstdDecoder;
+use bytes::Bytes;
+use futures_util::{stream::BoxStream, Stream, StreamExt};
+use tokio::io::AsyncReadExt;
+use tokio_util::io::{ReaderStream, StreamReader};
+
+/// Decompresses a stream of zstd-compressed content.
+///
+/// The first `offset` bytes of the decompressed content are skipped, so that
+/// part of the content can be served without storing it uncompressed.
+pub async fn decompress<S>(
+    stream: S,
+    offset: u64,
+) -> io::Result<BoxStream<'static, io::Result<Bytes>>>
+where
+    S: Stream<Item = io::Result<Bytes>> + Send + Unpin + 'static,
+{
+    let mut decoder = ZstdDecoder::new(StreamReader::new(stream));
+
+    if offset > 0 {
+        let skipped =
+            tokio::io::copy(&mut (&mut decoder).take(offset), &mut tokio::io::sink()).await?;
+        if skipped != offset {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "content ended before offset",
+            ));
+        }
+    }
+
+    Ok(ReaderStream::new(decoder).boxed())
+}
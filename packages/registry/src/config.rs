@@ -0,0 +1,73 @@
+use std::path::Path;
+
+use confique::Config as Confique;
+use serde::Deserialize;
+
+/// Registry config.
+#[derive(Confique)]
+pub struct Config {
+    /// The storage backend to use.
+    #[config(env = "ARMORY_STORAGE", default = "filesystem")]
+    pub storage: Backend,
+    /// The maximum size of a published artifact in bytes.
+    ///
+    /// The limit applies to the decompressed content, so compressed uploads
+    /// cannot be used to get around it. Defaults to 100 MiB.
+    #[config(env = "ARMORY_MAX_ARTIFACT_SIZE", default = 104857600)]
+    pub max_artifact_size: u64,
+    /// Settings for the S3 storage backend.
+    #[config(nested)]
+    pub s3: S3Config,
+}
+
+/// A storage backend.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Backend {
+    /// Stores data on the local filesystem.
+    Filesystem,
+    /// Stores data in an S3-compatible object store.
+    S3,
+}
+
+/// Settings for the S3 storage backend.
+///
+/// Unset values fall back to the standard `AWS_*` environment variables.
+#[derive(Confique)]
+pub struct S3Config {
+    /// The name of the bucket to store data in.
+    #[config(env = "ARMORY_S3_BUCKET")]
+    pub bucket: Option<String>,
+    /// The region of the bucket.
+    #[config(env = "ARMORY_S3_REGION")]
+    pub region: Option<String>,
+    /// The endpoint of the object store.
+    ///
+    /// Set this to use an S3-compatible store other than AWS, such as MinIO.
+    #[config(env = "ARMORY_S3_ENDPOINT")]
+    pub endpoint: Option<String>,
+    /// The access key ID to use for authentication.
+    #[config(env = "ARMORY_S3_ACCESS_KEY_ID")]
+    pub access_key_id: Option<String>,
+    /// The secret access key to use for authentication.
+    #[config(env = "ARMORY_S3_SECRET_ACCESS_KEY")]
+    pub secret_access_key: Option<String>,
+    /// Whether to allow unencrypted connections to the endpoint.
+    #[config(env = "ARMORY_S3_ALLOW_HTTP", default = false)]
+    pub allow_http: bool,
+}
+
+impl Config {
+    /// Loads config from various sources.
+    ///
+    /// Values are sourced in order of priority from: environment variables, a
+    /// config file in the armory home directory, and default values. Returns an
+    /// error if loading fails.
+    pub fn load(armory_home: &Path) -> anyhow::Result<Self> {
+        Config::builder()
+            .env()
+            .file(armory_home.join("config.toml"))
+            .load()
+            .map_err(|e| e.into())
+    }
+}
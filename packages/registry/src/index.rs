@@ -0,0 +1,429 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    sync::Arc,
+};
+
+use anyhow::Context;
+use model::{Format, IndexEntry, Metadata, Signature, TargetInfo, Triple};
+use serde::{Deserialize, Serialize};
+use tempfile::NamedTempFile;
+use tokio::sync::{Mutex, MutexGuard};
+use utils::{
+    requirement::ParseReqError,
+    validation::{is_reserved, validate_tag},
+    Version, VersionReq,
+};
+
+use crate::storage::{Object, Storage};
+
+/// The prefix under which package records are stored.
+pub const PACKAGES: &str = "packages";
+
+/// The prefix under which artifact blobs are stored.
+pub const BLOBS: &str = "blobs";
+
+/// The registry index.
+///
+/// Artifacts are stored once as blobs addressed by the SHA-256 digest of their
+/// content, at `blobs/{digest}.zst`, compressed with zstd. Artifacts published
+/// before compression was introduced remain uncompressed at `blobs/{digest}`.
+/// Each package has a record at
+/// `packages/{name}.json` that maps its versions and triples to those blobs, so
+/// identical artifacts published under several versions or triples share a
+/// single blob.
+#[derive(Clone)]
+pub struct Index {
+    storage: Arc<dyn Storage>,
+    lock: Arc<Mutex<()>>,
+}
+
+/// A package record.
+///
+/// Describes every published version of a package.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PackageRecord {
+    /// The name of the package.
+    pub name: String,
+    /// The users that are allowed to publish new versions of the package.
+    ///
+    /// The first user to publish the package becomes its owner. Packages
+    /// migrated from the legacy layout start out without owners, and only
+    /// admins can publish to them until they are given one.
+    #[serde(default)]
+    pub owners: Vec<String>,
+    /// The published versions of the package, keyed by version.
+    pub versions: BTreeMap<String, VersionRecord>,
+    /// The versions that the tags of the package point to, keyed by tag.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub tags: BTreeMap<String, String>,
+}
+
+/// A record of a published version of a package.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct VersionRecord {
+    /// The artifacts published for the version, keyed by triple.
+    pub targets: BTreeMap<Triple, TargetRecord>,
+    /// Whether the version has been yanked.
+    ///
+    /// Yanked versions are never resolved as the latest version of a package
+    /// but can still be fetched explicitly.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub yanked: bool,
+    /// Descriptive metadata about the version.
+    ///
+    /// Updated whenever an artifact is published for the version.
+    #[serde(default)]
+    pub metadata: Metadata,
+}
+
+/// A record of an artifact published for a specific triple.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TargetRecord {
+    /// The SHA-256 digest of the artifact, encoded as lowercase hex.
+    pub digest: String,
+    /// The size of the artifact in bytes.
+    pub size: u64,
+    /// The size of the compressed blob in bytes.
+    ///
+    /// Blobs stored before compression was introduced are uncompressed and
+    /// have no compressed size.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub compressed_size: Option<u64>,
+    /// The publisher signature over the artifact, if it is signed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature: Option<Signature>,
+    /// When the artifact was published, in seconds since the Unix epoch.
+    ///
+    /// Artifacts migrated from the legacy layout have no publish time.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub published_at: Option<u64>,
+    /// The format of the artifact.
+    #[serde(default, skip_serializing_if = "is_binary")]
+    pub format: Format,
+}
+
+impl PackageRecord {
+    /// Creates a record for a package with no versions.
+    pub fn new(name: String) -> Self {
+        Self {
+            name,
+            owners: Vec::new(),
+            versions: BTreeMap::new(),
+            tags: BTreeMap::new(),
+        }
+    }
+
+    /// Checks whether a user is an owner of the package.
+    pub fn is_owner(&self, user: &str) -> bool {
+        self.owners.iter().any(|owner| owner == user)
+    }
+
+    /// Checks whether a user can publish a new artifact of the package.
+    ///
+    /// Owners and admins can always publish. A package without owners can only
+    /// be claimed by any user if it is brand new, since packages that already
+    /// have versions (such as those migrated from the legacy layout) would
+    /// otherwise be up for grabs. Reserved names can only be claimed by admins.
+    pub fn can_publish(&self, user: &str, is_admin: bool) -> bool {
+        if is_admin {
+            return true;
+        }
+
+        if !self.owners.is_empty() {
+            return self.is_owner(user);
+        }
+
+        self.versions.is_empty() && !is_reserved(&self.name)
+    }
+
+    /// Returns every published version along with its record.
+    ///
+    /// Versions are returned in ascending order.
+    pub fn sorted_versions(&self) -> Vec<(&String, &VersionRecord)> {
+        let mut versions: Vec<_> = self.versions.iter().collect();
+        versions.sort_by_cached_key(|(version, _)| Version::parse(version));
+        versions
+    }
+
+    /// Returns the versions that are available for a triple.
+    ///
+    /// Versions are returned in ascending order. Yanked versions are excluded.
+    pub fn versions_for(&self, triple: &Triple) -> Vec<String> {
+        self.sorted_versions()
+            .into_iter()
+            .filter(|(_, record)| !record.yanked && record.targets.contains_key(triple))
+            .map(|(version, _)| version.clone())
+            .collect()
+    }
+
+    /// Resolves a version requirement or tag for a triple.
+    ///
+    /// Returns the highest available version that satisfies the requirement,
+    /// skipping yanked versions. A requirement that names a published version
+    /// exactly resolves to that version even if it has been yanked, as does a
+    /// version that is not a valid requirement on its own. A tag resolves to
+    /// the version that it points to, unless that version has been yanked.
+    pub fn resolve(
+        &self,
+        requirement: &str,
+        triple: &Triple,
+    ) -> Result<Option<String>, ParseReqError> {
+        if let Some(version) = self.tags.get(requirement) {
+            let available = self
+                .versions
+                .get(version)
+                .is_some_and(|record| !record.yanked && record.targets.contains_key(triple));
+            return Ok(available.then(|| version.clone()));
+        }
+
+        if self.target(requirement, triple).is_some() {
+            return Ok(Some(requirement.to_owned()));
+        }
+
+        // a tag that does not exist matches nothing
+        if validate_tag(requirement).is_ok() {
+            return Ok(None);
+        }
+
+        let requirement = VersionReq::parse(requirement)?;
+        let versions = self.versions_for(triple);
+
+        Ok(requirement
+            .best_match(versions.iter().map(String::as_str))
+            .map(str::to_owned))
+    }
+
+    /// Returns the digests of every artifact published for the package.
+    pub fn digests(&self) -> impl Iterator<Item = &str> {
+        self.versions
+            .values()
+            .flat_map(|version| version.targets.values())
+            .map(|target| target.digest.as_str())
+    }
+
+    /// Returns the entries of the index of the package.
+    ///
+    /// There is one entry per version in ascending order, including yanked
+    /// versions.
+    pub fn index_entries(&self) -> Vec<IndexEntry> {
+        self.sorted_versions()
+            .into_iter()
+            .map(|(version, record)| IndexEntry {
+                version: version.clone(),
+                yanked: record.yanked,
+                metadata: record.metadata.clone(),
+                targets: record
+                    .targets
+                    .iter()
+                    .map(|(triple, target)| target.info(triple))
+                    .collect(),
+                tags: self
+                    .tags
+                    .iter()
+                    .filter(|(_, tagged)| *tagged == version)
+                    .map(|(tag, _)| tag.clone())
+                    .collect(),
+            })
+            .collect()
+    }
+
+    /// Removes the tags that point to versions that no longer exist.
+    pub fn prune_tags(&mut self) {
+        let versions = &self.versions;
+        self.tags
+            .retain(|_, version| versions.contains_key(version));
+    }
+
+    /// Returns the artifact published for a version and triple, if any.
+    pub fn target(&self, version: &str, triple: &Triple) -> Option<&TargetRecord> {
+        self.versions.get(version)?.targets.get(triple)
+    }
+}
+
+impl TargetRecord {
+    /// Returns public information about the artifact.
+    pub fn info(&self, triple: &Triple) -> TargetInfo {
+        TargetInfo {
+            triple: triple.clone(),
+            size: self.size,
+            compressed_size: self.compressed_size,
+            checksum: self.digest.clone(),
+            published_at: self.published_at,
+            signed: self.signature.is_some(),
+            format: self.format,
+        }
+    }
+}
+
+impl Index {
+    /// Creates an index on top of a storage backend.
+    pub fn new(storage: Arc<dyn Storage>) -> Self {
+        Self {
+            storage,
+            lock: Arc::new(Mutex::new(())),
+        }
+    }
+
+    /// Acquires exclusive access to the index for writing.
+    ///
+    /// Package records are updated by reading, modifying, and writing them
+    /// back, so the guard should be held across the whole update.
+    pub async fn lock(&self) -> MutexGuard<'_, ()> {
+        self.lock.lock().await
+    }
+
+    /// Lists the names of all packages in the index.
+    pub async fn packages(&self) -> anyhow::Result<Vec<String>> {
+        let mut names: Vec<_> = self
+            .storage
+            .list(PACKAGES)
+            .await?
+            .into_iter()
+            .filter_map(|key| key.strip_suffix(".json").map(|name| name.to_owned()))
+            .collect();
+        names.sort();
+        Ok(names)
+    }
+
+    /// Loads the record for a package.
+    ///
+    /// Returns `None` if the package does not exist.
+    pub async fn package(&self, name: &str) -> anyhow::Result<Option<PackageRecord>> {
+        let Some(content) = self.storage.read(&record_key(name)).await? else {
+            return Ok(None);
+        };
+
+        let record = serde_json::from_slice(&content)
+            .with_context(|| format!("failed to parse record for package {name}"))?;
+
+        Ok(Some(record))
+    }
+
+    /// Saves the record for a package.
+    pub async fn save(&self, record: &PackageRecord) -> anyhow::Result<()> {
+        let content = serde_json::to_vec_pretty(record).context("failed to serialize record")?;
+        self.storage.write(&record_key(&record.name), content).await
+    }
+
+    /// Deletes the record for a package.
+    pub async fn remove(&self, name: &str) -> anyhow::Result<()> {
+        self.storage.delete(&record_key(name)).await
+    }
+
+    /// Stores a compressed artifact blob.
+    ///
+    /// If a blob with the same digest already exists, the staged file is
+    /// discarded instead.
+    pub async fn put_blob(&self, digest: &str, staged: NamedTempFile) -> anyhow::Result<()> {
+        let key = compressed_blob_key(digest);
+        if self.storage.exists(&key).await? {
+            return Ok(());
+        }
+        self.storage.put(&key, staged).await
+    }
+
+    /// Deletes the blobs that are no longer referenced by any package record.
+    ///
+    /// Only the given digests are considered, since other blobs are known to
+    /// be in use. The index should be locked by the caller.
+    pub async fn prune_blobs(&self, digests: BTreeSet<String>) -> anyhow::Result<()> {
+        let mut unused = digests;
+        for name in self.packages().await? {
+            if unused.is_empty() {
+                break;
+            }
+            if let Some(record) = self.package(&name).await? {
+                for digest in record.digests() {
+                    unused.remove(digest);
+                }
+            }
+        }
+
+        for digest in unused {
+            self.storage.delete(&compressed_blob_key(&digest)).await?;
+            self.storage.delete(&blob_key(&digest)).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Loads the blob of an artifact from an offset onwards.
+    ///
+    /// The content is returned as stored, so it is compressed if the target
+    /// has a compressed size. Returns `None` if the blob does not exist.
+    pub async fn blob_from(
+        &self,
+        target: &TargetRecord,
+        offset: u64,
+    ) -> anyhow::Result<Option<Object>> {
+        let key = match target.compressed_size {
+            Some(_) => compressed_blob_key(&target.digest),
+            None => blob_key(&target.digest),
+        };
+        self.storage.get_from(&key, offset).await
+    }
+}
+
+/// Checks whether an artifact is a single binary, which is the default format.
+fn is_binary(format: &Format) -> bool {
+    *format == Format::Binary
+}
+
+/// Returns the storage key of a package record.
+fn record_key(name: &str) -> String {
+    format!("{PACKAGES}/{name}.json")
+}
+
+/// Returns the storage key of an uncompressed blob.
+fn blob_key(digest: &str) -> String {
+    format!("{BLOBS}/{digest}")
+}
+
+/// Returns the storage key of a compressed blob.
+fn compressed_blob_key(digest: &str) -> String {
+    format!("{BLOBS}/{digest}.zst")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{PackageRecord, VersionRecord};
+
+    #[test]
+    fn new_packages_are_claimed_by_first_publisher() {
+        let record = PackageRecord::new("ripgrep".to_owned());
+        assert!(record.can_publish("alice", false));
+        assert!(record.can_publish("alice", true));
+    }
+
+    #[test]
+    fn owned_packages() {
+        let mut record = PackageRecord::new("ripgrep".to_owned());
+        record.owners.push("alice".to_owned());
+        record
+            .versions
+            .insert("1.0.0".to_owned(), VersionRecord::default());
+
+        assert!(record.can_publish("alice", false));
+        assert!(!record.can_publish("bob", false));
+        assert!(record.can_publish("bob", true));
+    }
+
+    #[test]
+    fn ownerless_packages_with_versions_are_admin_only() {
+        // packages migrated from the legacy layout have versions but no owners
+        let mut record = PackageRecord::new("ripgrep".to_owned());
+        record
+            .versions
+            .insert("1.0".to_owned(), VersionRecord::default());
+
+        assert!(!record.can_publish("mallory", false));
+        assert!(record.can_publish("admin", true));
+    }
+
+    #[test]
+    fn reserved_names_are_admin_only() {
+        let record = PackageRecord::new("armory".to_owned());
+        assert!(!record.can_publish("alice", false));
+        assert!(record.can_publish("admin", true));
+    }
+}
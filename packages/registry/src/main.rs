@@ -1,29 +1,62 @@
+mod compression;
+mod config;
+mod index;
+mod migrate;
+mod staging;
+mod storage;
+mod tokens;
+
 use std::{
+    collections::BTreeSet,
     env::VarError,
-    fs,
+    fs, io,
     path::{Path, PathBuf},
     sync::Arc,
+    time::{Duration, SystemTime, UNIX_EPOCH},
 };
 
 use anyhow::{bail, Context};
 use axum::{
-    extract::{DefaultBodyLimit, Request, State},
-    http::{HeaderMap, HeaderValue},
+    async_trait,
+    body::Body,
+    extract::{FromRequest, Path as UrlPath, Query, Request, State},
+    http::{
+        header::{
+            ACCEPT_ENCODING, ACCEPT_RANGES, CACHE_CONTROL, CONTENT_ENCODING, CONTENT_LENGTH,
+            CONTENT_RANGE, CONTENT_TYPE, ETAG, IF_NONE_MATCH, IF_RANGE, LAST_MODIFIED, RANGE, VARY,
+        },
+        HeaderMap, HeaderValue, Method, StatusCode,
+    },
     middleware::{self, Next},
     response::{IntoResponse, Response},
-    routing::post,
-    Json, Router,
+    routing::{self, post},
+    Extension, Json, Router,
 };
-use base64::prelude::*;
+use futures_util::{StreamExt, TryStreamExt};
 use model::{
-    ErrorInfo, GeneralError, GetError, GetInfoError, GetInfoInput, GetInfoOutput, GetInput,
-    GetOutput, ListError, ListInput, ListOutput, PublishError, PublishInput, PublishOutput,
+    AddOwnerError, AddOwnerInput, AddOwnerOutput, CreateTokenError, CreateTokenInput,
+    CreateTokenOutput, ErrorInfo, GeneralError, GetError, GetIndexError, GetInfoError,
+    GetInfoInput, GetInfoOutput, GetInput, GetOutput, ListError, ListInput, ListOutput,
+    ListOwnersError, ListOwnersInput, ListOwnersOutput, ListTokensError, ListTokensInput,
+    ListTokensOutput, PackageSummary, PublishError, PublishInput, PublishOutput, ReleaseInfo,
+    RemoveOwnerError, RemoveOwnerInput, RemoveOwnerOutput, ResolveError, ResolveInput,
+    ResolveOutput, RevokeTokenError, RevokeTokenInput, RevokeTokenOutput, Scope, SearchError,
+    SearchInput, SearchOutput, TagError, TagInput, TagOutput, UnpublishError, UnpublishInput,
+    UnpublishOutput, YankError, YankInput, YankOutput,
 };
-use serde::Serialize;
+use serde::{de::DeserializeOwned, Serialize};
 use sha2::{Digest, Sha256};
 use tower_http::trace::TraceLayer;
 use tracing::{error, info, warn};
-use utils::sort_versions;
+use utils::validation::{validate_name, validate_semver, validate_tag, validate_version};
+
+use crate::{
+    config::Config,
+    index::{Index, PackageRecord, TargetRecord},
+    staging::{stage, TooLarge},
+    storage::Storage,
+    tokens::Tokens,
+};
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
@@ -39,20 +72,71 @@ async fn main() -> anyhow::Result<()> {
 
     create_armory_dirs(&armory_home).context("failed to create armory directories")?;
 
+    let config = Config::load(&armory_home).context("failed to load config")?;
+
+    info!("storage = {:?}", config.storage);
+
+    let storage: Arc<dyn Storage> = storage::from_config(&config, &armory_home)
+        .context("failed to initialize storage")?
+        .into();
+
+    let index = Index::new(storage.clone());
+
+    migrate::migrate(storage.as_ref(), &index, &armory_home.join("staging"))
+        .await
+        .context("failed to migrate legacy artifacts")?;
+
+    let tokens = Tokens::load(storage.clone())
+        .await
+        .context("failed to load tokens")?;
+
     let password = load_password()?;
     let state = AppState {
         armory_home: Arc::new(armory_home),
+        index,
+        tokens,
         password,
+        max_artifact_size: config.max_artifact_size,
     };
 
-    let app = Router::new()
+    let read = Router::new()
+        .route("/get", post(get).get(get))
+        .route("/resolve", post(resolve).get(resolve))
+        .route("/get-info", post(get_info).get(get_info))
+        .route("/list", post(list).get(list))
+        .route("/index/:name", routing::get(get_index))
+        .route("/search", post(search).get(search))
+        .route("/list-owners", post(list_owners).get(list_owners))
+        .route_layer(middleware::from_fn(|request: Request, next: Next| {
+            authorize(Scope::Read, request, next)
+        }))
+        .route_layer(middleware::from_fn(conditional));
+
+    let publish = Router::new()
         .route("/publish", post(publish))
-        .route("/get", post(get))
-        .route("/get-info", post(get_info))
-        .route("/list", post(list))
+        .route("/add-owner", post(add_owner))
+        .route("/remove-owner", post(remove_owner))
+        .route("/yank", post(yank))
+        .route("/tag", post(tag))
+        .route_layer(middleware::from_fn(|request: Request, next: Next| {
+            authorize(Scope::Publish, request, next)
+        }));
+
+    let admin = Router::new()
+        .route("/create-token", post(create_token))
+        .route("/list-tokens", post(list_tokens))
+        .route("/revoke-token", post(revoke_token))
+        .route("/unpublish", post(unpublish))
+        .route_layer(middleware::from_fn(|request: Request, next: Next| {
+            authorize(Scope::Admin, request, next)
+        }));
+
+    let app = Router::new()
+        .merge(read)
+        .merge(publish)
+        .merge(admin)
         .with_state(state.clone())
         .layer(middleware::from_fn_with_state(state, authentication))
-        .layer(DefaultBodyLimit::max(1024 * 1024 * 100))
         .layer(TraceLayer::new_for_http());
 
     let listener = tokio::net::TcpListener::bind("0.0.0.0:3000").await?;
@@ -66,59 +150,169 @@ async fn main() -> anyhow::Result<()> {
 
 /// Loads the registry password from the environment.
 fn load_password() -> anyhow::Result<Option<String>> {
-    return match std::env::var("ARMORY_PASSWORD") {
+    match std::env::var("ARMORY_PASSWORD") {
         Ok(password) => Ok(Some(password)),
         Err(VarError::NotPresent) => {
             warn!("no password configured; set ARMORY_PASSWORD to set a password");
             Ok(None)
         }
         Err(VarError::NotUnicode(_)) => bail!("password is not valid unicode"),
-    };
+    }
 }
 
 fn create_armory_dirs(armory_home: &Path) -> anyhow::Result<()> {
     fs::create_dir_all(armory_home).context("failed to create armory home directory")?;
-    fs::create_dir_all(armory_home.join("registry"))
-        .context("failed to create armory registry directory")?;
+    fs::create_dir_all(armory_home.join("staging"))
+        .context("failed to create armory staging directory")?;
     Ok(())
 }
 
 /// Global application state.
-#[derive(Clone, Debug)]
+#[derive(Clone)]
 struct AppState {
     /// Home directory of the application.
     armory_home: Arc<PathBuf>,
+    /// Index of packages and artifacts in the registry.
+    index: Index,
+    /// API tokens that can be used for authentication.
+    tokens: Tokens,
     /// Registry password.
+    ///
+    /// This is the bootstrap credential for the registry and grants every
+    /// scope.
     password: Option<String>,
+    /// The maximum size of a published artifact in bytes.
+    max_artifact_size: u64,
 }
 
 pub mod header {
     /// Indicates the success or failure of an operation.
     ///
     /// Should be set to `true` or `false`.
-    pub const OK: &'static str = "x-ok";
+    pub const OK: &str = "x-ok";
     /// The password to use for authentication.
-    pub const PASSWORD: &'static str = "x-password";
+    pub const PASSWORD: &str = "x-password";
+    /// The API token to use for authentication.
+    pub const TOKEN: &str = "x-token";
+    /// The JSON-encoded input of an operation that streams its request body.
+    pub const INPUT: &str = "x-input";
+    /// The JSON-encoded output of an operation that streams its response body.
+    pub const OUTPUT: &str = "x-output";
 }
 
-/// An authentication layer.
-async fn authentication(state: State<AppState>, request: Request, next: Next) -> Response {
-    if let Some(password) = state.password.as_ref() {
-        let provided = request
-            .headers()
-            .get(header::PASSWORD)
-            .and_then(|v| v.to_str().ok());
+/// Operation input.
+///
+/// Parsed from the JSON body of POST requests, and from the query string of GET
+/// requests so that read operations can be cached.
+struct Input<T>(T);
+
+#[async_trait]
+impl<T, S> FromRequest<S> for Input<T>
+where
+    T: DeserializeOwned,
+    S: Send + Sync,
+{
+    type Rejection = Response;
 
-        if provided.is_none() {
-            return Error(GeneralError::PasswordMissing).into_response();
+    async fn from_request(request: Request, state: &S) -> Result<Self, Self::Rejection> {
+        if request.method() == Method::GET {
+            let Query(input) = Query::<T>::from_request(request, state)
+                .await
+                .map_err(IntoResponse::into_response)?;
+            return Ok(Input(input));
         }
 
-        if provided.unwrap() != password {
+        let Json(input) = Json::<T>::from_request(request, state)
+            .await
+            .map_err(IntoResponse::into_response)?;
+        Ok(Input(input))
+    }
+}
+
+/// Parses the JSON-encoded operation input from the `x-input` header.
+///
+/// Returns `None` if the header is missing or malformed.
+fn parse_input<T>(headers: &HeaderMap) -> Option<T>
+where
+    T: DeserializeOwned,
+{
+    let value = headers.get(header::INPUT)?;
+    serde_json::from_slice(value.as_bytes()).ok()
+}
+
+/// The identity of the caller of an operation.
+#[derive(Clone, Debug)]
+struct Identity {
+    /// The user that the caller authenticated as.
+    user: String,
+    /// The scopes granted to the caller.
+    scopes: Vec<Scope>,
+}
+
+impl Identity {
+    /// Checks whether the caller has the admin scope.
+    fn is_admin(&self) -> bool {
+        self.scopes
+            .iter()
+            .any(|granted| granted.grants(Scope::Admin))
+    }
+}
+
+/// An authentication layer.
+///
+/// Authenticates the caller with an API token or the registry password and
+/// records their identity on the request. If neither a password nor any tokens
+/// are configured, the registry is open and every caller is granted every
+/// scope.
+async fn authentication(state: State<AppState>, mut request: Request, next: Next) -> Response {
+    let headers = request.headers();
+    let token = headers.get(header::TOKEN).and_then(|v| v.to_str().ok());
+    let password = headers.get(header::PASSWORD).and_then(|v| v.to_str().ok());
+
+    let identity = if state.password.is_none() && state.tokens.is_empty().await {
+        Identity {
+            user: String::from("anonymous"),
+            scopes: vec![Scope::Admin],
+        }
+    } else if let Some(token) = token {
+        let Some(record) = state.tokens.authenticate(token).await else {
+            return Error(GeneralError::TokenInvalid).into_response();
+        };
+        Identity {
+            user: record.user,
+            scopes: record.scopes,
+        }
+    } else if let Some(password) = password {
+        if state.password.as_deref() != Some(password) {
             return Error(GeneralError::PasswordInvalid).into_response();
         }
+        Identity {
+            user: String::from("admin"),
+            scopes: vec![Scope::Admin],
+        }
+    } else {
+        return Error(GeneralError::CredentialsMissing).into_response();
+    };
+
+    request.extensions_mut().insert(identity);
+    next.run(request).await
+}
+
+/// An authorization layer.
+///
+/// Rejects callers whose identity does not grant the required scope. Must run
+/// after the authentication layer.
+async fn authorize(scope: Scope, request: Request, next: Next) -> Response {
+    let identity = request
+        .extensions()
+        .get::<Identity>()
+        .expect("identity should be set by authentication layer");
+
+    if !identity.scopes.iter().any(|granted| granted.grants(scope)) {
+        return Error(GeneralError::PermissionDenied).into_response();
     }
-    let response = next.run(request).await;
-    response
+
+    next.run(request).await
 }
 
 /// Output response.
@@ -165,186 +359,1176 @@ where
     }
 }
 
+/// Streaming output response.
+///
+/// Sets the `x-ok` header to `true`, serializes the output to JSON in the
+/// `x-output` header, and streams the content as the body of the response. The
+/// output value must implement `Serialize`. The `ETag` and `Last-Modified`
+/// headers are set so that the response can be cached.
+///
+/// If the body starts at an offset, the response is `206 Partial Content` and
+/// describes the range of the content that it carries. If the body is
+/// compressed, the `Content-Encoding` header is set to `zstd`.
+struct Content<T> {
+    output: T,
+    /// The size of the content in bytes, as sent.
+    length: u64,
+    /// The offset in the content that the body starts at.
+    offset: u64,
+    /// Whether the content is compressed with zstd.
+    compressed: bool,
+    body: Body,
+    etag: HeaderValue,
+    last_modified: Option<SystemTime>,
+}
+
+impl<T> IntoResponse for Content<T>
+where
+    T: Serialize,
+{
+    fn into_response(self) -> Response {
+        let output = serde_json::to_vec(&self.output).expect("output should serialize");
+
+        let headers = {
+            let mut map = HeaderMap::new();
+            map.insert(header::OK, HeaderValue::from_static("true"));
+            map.insert(
+                header::OUTPUT,
+                HeaderValue::from_bytes(&output).expect("json should be a valid header value"),
+            );
+            map.insert(
+                CONTENT_TYPE,
+                HeaderValue::from_static("application/octet-stream"),
+            );
+            map.insert(CONTENT_LENGTH, HeaderValue::from(self.length - self.offset));
+            map.insert(ACCEPT_RANGES, HeaderValue::from_static("bytes"));
+            if self.compressed {
+                map.insert(CONTENT_ENCODING, HeaderValue::from_static("zstd"));
+            }
+            map.insert(ETAG, self.etag);
+            if let Some(last_modified) = self.last_modified {
+                map.insert(
+                    LAST_MODIFIED,
+                    HeaderValue::from_str(&httpdate::fmt_http_date(last_modified))
+                        .expect("date should be a valid header value"),
+                );
+            }
+            map
+        };
+
+        if self.offset == 0 {
+            return (headers, self.body).into_response();
+        }
+
+        let mut headers = headers;
+        headers.insert(
+            CONTENT_RANGE,
+            HeaderValue::from_str(&format!(
+                "bytes {}-{}/{}",
+                self.offset,
+                self.length - 1,
+                self.length
+            ))
+            .expect("range should be a valid header value"),
+        );
+
+        (StatusCode::PARTIAL_CONTENT, headers, self.body).into_response()
+    }
+}
+
+/// Document response.
+///
+/// Sets the `x-ok` header to `true` and returns the content as the body of the
+/// response.
+struct Document {
+    content_type: &'static str,
+    content: Vec<u8>,
+}
+
+impl IntoResponse for Document {
+    fn into_response(self) -> Response {
+        let headers = {
+            let mut map = HeaderMap::new();
+            map.insert(header::OK, HeaderValue::from_static("true"));
+            map.insert(CONTENT_TYPE, HeaderValue::from_static(self.content_type));
+            map
+        };
+
+        (headers, self.content).into_response()
+    }
+}
+
+/// Not modified response.
+///
+/// Sets the `x-ok` header to `true` and the `ETag` header, and returns
+/// `304 Not Modified` without a body.
+struct NotModified {
+    etag: HeaderValue,
+}
+
+impl IntoResponse for NotModified {
+    fn into_response(self) -> Response {
+        let headers = {
+            let mut map = HeaderMap::new();
+            map.insert(header::OK, HeaderValue::from_static("true"));
+            map.insert(ETAG, self.etag);
+            map
+        };
+
+        (StatusCode::NOT_MODIFIED, headers).into_response()
+    }
+}
+
+/// A conditional request layer.
+///
+/// Makes successful responses to GET requests cacheable. Responses are tagged
+/// with an ETag, derived from the body unless the handler already set one, and
+/// must be revalidated before a cache reuses them. If the ETag matches the
+/// request's `If-None-Match` header, the response is replaced by
+/// `304 Not Modified`.
+async fn conditional(request: Request, next: Next) -> Response {
+    if request.method() != Method::GET {
+        return next.run(request).await;
+    }
+
+    let if_none_match = request.headers().get(IF_NONE_MATCH).cloned();
+    let response = next.run(request).await;
+
+    if response.headers().get(header::OK).map(|v| v.as_bytes()) != Some(b"true") {
+        return response;
+    }
+
+    let (mut parts, mut body) = response.into_parts();
+
+    if parts.status == StatusCode::OK && !parts.headers.contains_key(ETAG) {
+        let content = match axum::body::to_bytes(body, usize::MAX).await {
+            Ok(content) => content,
+            Err(e) => {
+                error!("internal failure: {e:?}");
+                return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+            }
+        };
+        parts.headers.insert(ETAG, etag(&content));
+        body = Body::from(content);
+    }
+
+    parts
+        .headers
+        .insert(CACHE_CONTROL, HeaderValue::from_static("no-cache"));
+    parts.headers.insert(
+        VARY,
+        HeaderValue::from_static("accept-encoding, x-token, x-password"),
+    );
+
+    let not_modified = match (&if_none_match, parts.headers.get(ETAG)) {
+        (Some(condition), Some(etag)) => etag_matches(condition, etag),
+        _ => false,
+    };
+
+    if parts.status == StatusCode::OK && not_modified {
+        parts.status = StatusCode::NOT_MODIFIED;
+        parts.headers.remove(CONTENT_LENGTH);
+        return Response::from_parts(parts, Body::empty());
+    }
+
+    Response::from_parts(parts, body)
+}
+
+/// Returns an ETag for some content.
+///
+/// The ETag is the SHA-256 digest of the content, encoded as hex.
+fn etag(content: &[u8]) -> HeaderValue {
+    let etag = format!("\"{}\"", hex::encode(Sha256::digest(content)));
+    HeaderValue::from_str(&etag).expect("etag should be a valid header value")
+}
+
+/// Returns the ETag of the zstd-encoded representation of some content, given
+/// the ETag of the content itself.
+///
+/// The two representations are different bytes, so they must not share a
+/// strong ETag. The encoded one is tagged with a `-zstd` suffix.
+fn zstd_etag(etag: &HeaderValue) -> HeaderValue {
+    let tag = etag
+        .to_str()
+        .ok()
+        .and_then(|etag| etag.strip_suffix('"'))
+        .expect("etag should be quoted");
+    HeaderValue::from_str(&format!("{tag}-zstd\"")).expect("etag should be a valid header value")
+}
+
+/// Checks whether a request accepts content compressed with zstd.
+fn accepts_zstd(headers: &HeaderMap) -> bool {
+    headers
+        .get_all(ACCEPT_ENCODING)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .any(|coding| {
+            let mut params = coding.split(';').map(str::trim);
+            params.next() == Some("zstd")
+                && !params.any(|param| {
+                    param.strip_prefix("q=").and_then(|q| q.parse::<f32>().ok()) == Some(0.0)
+                })
+        })
+}
+
+/// Returns the offset that a request asks for content to start at.
+///
+/// Only open-ended byte ranges (`Range: bytes={offset}-`) are supported, which
+/// is what resuming a download requires. If the request has an `If-Range`
+/// header, the range only applies if it matches the ETag exactly. Returns
+/// `None` if the request does not ask for a supported range.
+fn range_start(headers: &HeaderMap, etag: &HeaderValue) -> Option<u64> {
+    if headers.get(IF_RANGE).is_some_and(|value| value != etag) {
+        return None;
+    }
+
+    let range = headers.get(RANGE)?.to_str().ok()?;
+    let offset = range.strip_prefix("bytes=")?.strip_suffix('-')?;
+    offset.parse().ok()
+}
+
+/// Checks whether an `If-None-Match` header matches an ETag.
+///
+/// The header can list several ETags. Weak ETags match their strong
+/// counterparts, as is required for `If-None-Match`.
+fn etag_matches(if_none_match: &HeaderValue, etag: &HeaderValue) -> bool {
+    let (Ok(condition), Ok(etag)) = (if_none_match.to_str(), etag.to_str()) else {
+        return false;
+    };
+
+    let etag = etag.trim_start_matches("W/");
+
+    condition
+        .split(',')
+        .map(|tag| tag.trim())
+        .any(|tag| tag == "*" || tag.trim_start_matches("W/") == etag)
+}
+
 /// Publishes a package to the registry.
+///
+/// The artifact is streamed from the request body into a staging file, then
+/// stored as a blob once it has been received in full. Artifacts that are
+/// already stored are not stored again. The body may be compressed with zstd,
+/// as indicated by the `Content-Encoding` header.
 async fn publish(
     State(state): State<AppState>,
-    Json(input): Json<PublishInput>,
+    Extension(identity): Extension<Identity>,
+    headers: HeaderMap,
+    body: Body,
 ) -> Result<Output<PublishOutput>, Error<PublishError>> {
     info!("handling publish request");
 
-    let Ok(content) = BASE64_STANDARD.decode(input.content.as_bytes()) else {
-        return Err(Error(PublishError::InvalidEncoding));
+    let Some(input) = parse_input::<PublishInput>(&headers) else {
+        return Err(Error(PublishError::InvalidInput));
     };
 
-    let artifact_path = state
-        .armory_home
-        .join("registry")
-        .join(format!("{}/{}/{}", input.name, input.triple, input.version));
+    if validate_name(&input.name).is_err() {
+        return Err(Error(PublishError::InvalidName));
+    }
+
+    // new versions must be semantic versions so that they are ordered and
+    // matched against requirements reliably
+    if validate_semver(&input.version).is_err() {
+        return Err(Error(PublishError::InvalidVersion));
+    }
+
+    let content = body.into_data_stream().map_err(io::Error::other).boxed();
+    let content = match headers.get(CONTENT_ENCODING).map(|v| v.as_bytes()) {
+        None => content,
+        Some(b"zstd") => match compression::decompress(content, 0).await {
+            Ok(content) => content,
+            Err(e) => {
+                error!("internal failure: {e:?}");
+                return Err(Error(PublishError::InternalError));
+            }
+        },
+        Some(_) => return Err(Error(PublishError::InvalidInput)),
+    };
+
+    let staging = state.armory_home.join("staging");
+    let staged = match stage(&staging, content, state.max_artifact_size).await {
+        Ok(staged) => staged,
+        Err(e) if e.is::<TooLarge>() => return Err(Error(PublishError::TooLarge)),
+        Err(e) => {
+            error!("internal failure: {e:?}");
+            return Err(Error(PublishError::InternalError));
+        }
+    };
+
+    let _guard = state.index.lock().await;
+
+    let mut record = match state.index.package(&input.name).await {
+        Ok(record) => record.unwrap_or_else(|| PackageRecord::new(input.name.clone())),
+        Err(e) => {
+            error!("internal failure: {e:?}");
+            return Err(Error(PublishError::InternalError));
+        }
+    };
+
+    if !record.can_publish(&identity.user, identity.is_admin()) {
+        return Err(Error(PublishError::NotOwner));
+    }
+
+    if record.owners.is_empty() {
+        record.owners.push(identity.user.clone());
+    }
+
+    let version = record.versions.entry(input.version.clone()).or_default();
 
-    if let Some(existing) = fs::read(&artifact_path).ok() {
-        let existing_hash = Sha256::digest(existing);
-        let incoming_hash = Sha256::digest(&content);
-        if incoming_hash != existing_hash {
+    if let Some(existing) = version.targets.get_mut(&input.triple) {
+        if existing.digest != staged.digest || existing.format != input.format {
             return Err(Error(PublishError::VersionExists));
         }
+
+        // an unsigned artifact can be signed by publishing it again, but an
+        // existing signature is never replaced
+        if existing.signature.is_some() || input.signature.is_none() {
+            info!("artifact is already published");
+            return Ok(Output(PublishOutput {
+                size: staged.size,
+                compressed_size: staged.compressed_size,
+            }));
+        }
+
+        existing.signature = input.signature;
+
+        if let Err(e) = state.index.save(&record).await {
+            error!("internal failure: {e:?}");
+            return Err(Error(PublishError::InternalError));
+        }
+
+        info!("attached signature to existing artifact");
+        return Ok(Output(PublishOutput {
+            size: staged.size,
+            compressed_size: staged.compressed_size,
+        }));
     }
 
-    if let Err(e) = fs::create_dir_all(artifact_path.parent().expect("path should have parent"))
-        .with_context(|| format!("failed to create artifact dir"))
-    {
+    let published_at = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("time should be after epoch")
+        .as_secs();
+
+    version.targets.insert(
+        input.triple.clone(),
+        TargetRecord {
+            digest: staged.digest.clone(),
+            size: staged.size,
+            compressed_size: Some(staged.compressed_size),
+            signature: input.signature,
+            published_at: Some(published_at),
+            format: input.format,
+        },
+    );
+    version.metadata = input.metadata;
+
+    if let Err(e) = state.index.put_blob(&staged.digest, staged.file).await {
         error!("internal failure: {e:?}");
         return Err(Error(PublishError::InternalError));
     }
 
-    if let Err(e) = fs::write(&artifact_path, content)
-        .with_context(|| format!("failed to write artifact to {}", artifact_path.display()))
-    {
+    if let Err(e) = state.index.save(&record).await {
         error!("internal failure: {e:?}");
         return Err(Error(PublishError::InternalError));
     }
 
-    info!("published artifact to {}", artifact_path.display());
+    info!(
+        "published {}-{}-{} as blob {} for user {}",
+        input.name, input.version, input.triple, staged.digest, identity.user
+    );
 
-    Ok(Output(PublishOutput {}))
+    Ok(Output(PublishOutput {
+        size: staged.size,
+        compressed_size: staged.compressed_size,
+    }))
 }
 
+/// Gets a package from the registry.
+///
+/// The artifact is streamed from storage as the body of the response.
 async fn get(
     State(state): State<AppState>,
-    Json(input): Json<GetInput>,
-) -> Result<Output<GetOutput>, Error<GetError>> {
+    headers: HeaderMap,
+    Input(input): Input<GetInput>,
+) -> Result<Response, Error<GetError>> {
     info!("handling get request");
 
-    let registry = state.armory_home.join("registry");
-    let target_dir = registry.join(&input.name).join(input.triple.to_string());
+    if validate_name(&input.name).is_err() {
+        return Err(Error(GetError::InvalidName));
+    }
 
-    if !target_dir.is_dir() {
-        return Err(Error(GetError::PackageNotFound));
+    if input
+        .version
+        .as_deref()
+        .is_some_and(|version| validate_version(version).is_err())
+    {
+        return Err(Error(GetError::InvalidVersion));
     }
 
+    let record = match state.index.package(&input.name).await {
+        Ok(Some(record)) => record,
+        Ok(None) => return Err(Error(GetError::PackageNotFound)),
+        Err(e) => {
+            error!("internal failure: {e:?}");
+            return Err(Error(GetError::InternalError));
+        }
+    };
+
     let version = match input.version {
         Some(version) => version,
-        None => {
-            let entries = match fs::read_dir(&target_dir)
-                .with_context(|| format!("failed to read target dir {}", target_dir.display()))
-            {
-                Ok(entries) => entries,
-                Err(e) => {
-                    error!("internal failure: {e}");
-                    return Err(Error(GetError::InternalError));
-                }
-            };
+        None => match record.versions_for(&input.triple).pop() {
+            Some(version) => version,
+            None => return Err(Error(GetError::PackageNotFound)),
+        },
+    };
 
-            let version = entries
-                .filter_map(Result::ok)
-                .map(|e| e.file_name().to_string_lossy().to_string())
-                .max_by(|x, y| x.cmp(&y))
-                .map(|e| e.to_owned());
+    let Some(target) = record.target(&version, &input.triple) else {
+        return Err(Error(GetError::PackageNotFound));
+    };
 
-            match version {
-                Some(version) => version,
-                None => return Err(Error(GetError::PackageNotFound)),
-            }
+    let output = GetOutput {
+        name: input.name,
+        checksum: target.digest.clone(),
+        signature: target.signature.clone(),
+        yanked: record.versions[&version].yanked,
+        format: target.format,
+        version,
+    };
+
+    // the output covers the checksum of the artifact, so its digest identifies
+    // the uncompressed artifact and a match can skip reading the blob
+    let etag = etag(&serde_json::to_vec(&output).expect("output should serialize"));
+
+    // an interrupted download can be resumed by requesting the rest of the
+    // artifact, and is served in full instead if the range cannot be honoured
+    let offset = match range_start(&headers, &etag) {
+        Some(offset) if offset < target.size => offset,
+        _ => 0,
+    };
+
+    // compressed blobs are sent as stored to clients that accept zstd and are
+    // decompressed on the fly otherwise, since ranges always refer to the
+    // uncompressed content
+    let stored_compressed = target.compressed_size.is_some();
+    let compressed = stored_compressed && offset == 0 && accepts_zstd(&headers);
+
+    let etag = match compressed {
+        true => zstd_etag(&etag),
+        false => etag,
+    };
+
+    if let Some(condition) = headers.get(IF_NONE_MATCH) {
+        if etag_matches(condition, &etag) {
+            return Ok(NotModified { etag }.into_response());
+        }
+    }
+
+    let stored_offset = match stored_compressed {
+        true => 0,
+        false => offset,
+    };
+
+    let object = match state.index.blob_from(target, stored_offset).await {
+        Ok(Some(object)) => object,
+        Ok(None) => {
+            error!("internal failure: blob {} is missing", target.digest);
+            return Err(Error(GetError::InternalError));
         }
+        Err(e) => {
+            error!("internal failure: {e:?}");
+            return Err(Error(GetError::InternalError));
+        }
+    };
+
+    let (length, stream) = match (stored_compressed, compressed) {
+        (true, true) => (object.length, object.stream),
+        (true, false) => match compression::decompress(object.stream, offset).await {
+            Ok(stream) => (target.size, stream),
+            Err(e) => {
+                error!("internal failure: {e:?}");
+                return Err(Error(GetError::InternalError));
+            }
+        },
+        (false, _) => (target.size, object.stream),
     };
 
-    let artifact_path = target_dir.join(&version);
+    let content = Content {
+        output,
+        length,
+        offset,
+        compressed,
+        body: Body::from_stream(stream),
+        etag,
+        last_modified: target
+            .published_at
+            .map(|secs| UNIX_EPOCH + Duration::from_secs(secs)),
+    };
 
-    let Ok(bytes) = fs::read(&artifact_path) else {
-        return Err(Error(GetError::PackageNotFound));
+    Ok(content.into_response())
+}
+
+/// Resolves a version requirement to a version of a package.
+///
+/// Requirements are resolved by the registry so that every client resolves
+/// them the same way.
+async fn resolve(
+    State(state): State<AppState>,
+    Input(input): Input<ResolveInput>,
+) -> Result<Output<ResolveOutput>, Error<ResolveError>> {
+    info!("handling resolve request");
+
+    if validate_name(&input.name).is_err() {
+        return Err(Error(ResolveError::InvalidName));
+    }
+
+    let record = match state.index.package(&input.name).await {
+        Ok(Some(record)) => record,
+        Ok(None) => return Err(Error(ResolveError::PackageNotFound)),
+        Err(e) => {
+            error!("internal failure: {e:?}");
+            return Err(Error(ResolveError::InternalError));
+        }
     };
 
-    let content = BASE64_STANDARD.encode(bytes);
+    let version = match record.resolve(&input.requirement, &input.triple) {
+        Ok(Some(version)) => version,
+        Ok(None) => return Err(Error(ResolveError::VersionNotFound)),
+        Err(_) => return Err(Error(ResolveError::InvalidRequirement)),
+    };
 
-    Ok(Output(GetOutput {
+    Ok(Output(ResolveOutput {
         name: input.name,
         version,
-        content,
     }))
 }
 
+/// Yanks a version of a package, or undoes a previous yank.
+///
+/// Only owners of the package and admins can yank versions.
+async fn yank(
+    State(state): State<AppState>,
+    Extension(identity): Extension<Identity>,
+    Json(input): Json<YankInput>,
+) -> Result<Output<YankOutput>, Error<YankError>> {
+    info!("handling yank request");
+
+    if validate_name(&input.name).is_err() {
+        return Err(Error(YankError::InvalidName));
+    }
+
+    if validate_version(&input.version).is_err() {
+        return Err(Error(YankError::InvalidVersion));
+    }
+
+    let _guard = state.index.lock().await;
+
+    let mut record = match state.index.package(&input.name).await {
+        Ok(Some(record)) => record,
+        Ok(None) => return Err(Error(YankError::PackageNotFound)),
+        Err(e) => {
+            error!("internal failure: {e:?}");
+            return Err(Error(YankError::InternalError));
+        }
+    };
+
+    if !record.is_owner(&identity.user) && !identity.is_admin() {
+        return Err(Error(YankError::NotOwner));
+    }
+
+    let Some(version) = record.versions.get_mut(&input.version) else {
+        return Err(Error(YankError::VersionNotFound));
+    };
+
+    version.yanked = input.yanked;
+
+    if let Err(e) = state.index.save(&record).await {
+        error!("internal failure: {e:?}");
+        return Err(Error(YankError::InternalError));
+    }
+
+    match input.yanked {
+        true => info!("yanked {}-{}", input.name, input.version),
+        false => info!("unyanked {}-{}", input.name, input.version),
+    }
+
+    Ok(Output(YankOutput {}))
+}
+
+/// Points a tag of a package at a version, or removes the tag.
+///
+/// Only owners of the package and admins can move its tags.
+async fn tag(
+    State(state): State<AppState>,
+    Extension(identity): Extension<Identity>,
+    Json(input): Json<TagInput>,
+) -> Result<Output<TagOutput>, Error<TagError>> {
+    info!("handling tag request");
+
+    if validate_name(&input.name).is_err() {
+        return Err(Error(TagError::InvalidName));
+    }
+
+    if validate_tag(&input.tag).is_err() {
+        return Err(Error(TagError::InvalidTag));
+    }
+
+    if input
+        .version
+        .as_deref()
+        .is_some_and(|version| validate_version(version).is_err())
+    {
+        return Err(Error(TagError::InvalidVersion));
+    }
+
+    let _guard = state.index.lock().await;
+
+    let mut record = match state.index.package(&input.name).await {
+        Ok(Some(record)) => record,
+        Ok(None) => return Err(Error(TagError::PackageNotFound)),
+        Err(e) => {
+            error!("internal failure: {e:?}");
+            return Err(Error(TagError::InternalError));
+        }
+    };
+
+    if !record.is_owner(&identity.user) && !identity.is_admin() {
+        return Err(Error(TagError::NotOwner));
+    }
+
+    let previous = match &input.version {
+        Some(version) => {
+            if !record.versions.contains_key(version) {
+                return Err(Error(TagError::VersionNotFound));
+            }
+            record.tags.insert(input.tag.clone(), version.clone())
+        }
+        None => match record.tags.remove(&input.tag) {
+            Some(previous) => Some(previous),
+            None => return Err(Error(TagError::TagNotFound)),
+        },
+    };
+
+    if let Err(e) = state.index.save(&record).await {
+        error!("internal failure: {e:?}");
+        return Err(Error(TagError::InternalError));
+    }
+
+    match &input.version {
+        Some(version) => info!("tagged {}-{version} as {}", input.name, input.tag),
+        None => info!("removed tag {} of {}", input.tag, input.name),
+    }
+
+    Ok(Output(TagOutput { previous }))
+}
+
+/// Removes a package, a version of a package, or a single artifact from the
+/// registry.
+///
+/// Versions left without artifacts are removed, as are packages left without
+/// versions. Blobs are deleted once no package refers to them.
+async fn unpublish(
+    State(state): State<AppState>,
+    Json(input): Json<UnpublishInput>,
+) -> Result<Output<UnpublishOutput>, Error<UnpublishError>> {
+    info!("handling unpublish request");
+
+    if validate_name(&input.name).is_err() {
+        return Err(Error(UnpublishError::InvalidName));
+    }
+
+    if input
+        .version
+        .as_deref()
+        .is_some_and(|version| validate_version(version).is_err())
+    {
+        return Err(Error(UnpublishError::InvalidVersion));
+    }
+
+    if input.version.is_none() && input.triple.is_some() {
+        return Err(Error(UnpublishError::InvalidInput));
+    }
+
+    let _guard = state.index.lock().await;
+
+    let mut record = match state.index.package(&input.name).await {
+        Ok(Some(record)) => record,
+        Ok(None) => return Err(Error(UnpublishError::PackageNotFound)),
+        Err(e) => {
+            error!("internal failure: {e:?}");
+            return Err(Error(UnpublishError::InternalError));
+        }
+    };
+
+    let removed: BTreeSet<String> = match (&input.version, &input.triple) {
+        (Some(version), Some(triple)) => {
+            let Some(version_record) = record.versions.get_mut(version) else {
+                return Err(Error(UnpublishError::VersionNotFound));
+            };
+            let Some(target) = version_record.targets.remove(triple) else {
+                return Err(Error(UnpublishError::TargetNotFound));
+            };
+            if version_record.targets.is_empty() {
+                record.versions.remove(version);
+            }
+            BTreeSet::from([target.digest])
+        }
+        (Some(version), None) => {
+            let Some(version_record) = record.versions.remove(version) else {
+                return Err(Error(UnpublishError::VersionNotFound));
+            };
+            version_record
+                .targets
+                .into_values()
+                .map(|target| target.digest)
+                .collect()
+        }
+        (None, _) => {
+            let digests = record.digests().map(|digest| digest.to_owned()).collect();
+            record.versions.clear();
+            digests
+        }
+    };
+
+    record.prune_tags();
+
+    let result = match record.versions.is_empty() {
+        true => state.index.remove(&record.name).await,
+        false => state.index.save(&record).await,
+    };
+
+    if let Err(e) = result {
+        error!("internal failure: {e:?}");
+        return Err(Error(UnpublishError::InternalError));
+    }
+
+    if let Err(e) = state.index.prune_blobs(removed).await {
+        error!("internal failure: {e:?}");
+        return Err(Error(UnpublishError::InternalError));
+    }
+
+    match (&input.version, &input.triple) {
+        (Some(version), Some(triple)) => info!("unpublished {}-{version}-{triple}", input.name),
+        (Some(version), None) => info!("unpublished {}-{version}", input.name),
+        (None, _) => info!("unpublished {}", input.name),
+    }
+
+    Ok(Output(UnpublishOutput {}))
+}
+
+/// Gets information about a package from the registry.
 async fn get_info(
     State(state): State<AppState>,
-    Json(input): Json<GetInfoInput>,
+    Input(input): Input<GetInfoInput>,
 ) -> Result<Output<GetInfoOutput>, Error<GetInfoError>> {
     info!("handling get info request");
 
-    let registry = state.armory_home.join("registry");
-    let target_dir = registry.join(&input.name).join(input.triple.to_string());
-
-    if !target_dir.is_dir() {
-        return Err(Error(GetInfoError::PackageNotFound));
+    if validate_name(&input.name).is_err() {
+        return Err(Error(GetInfoError::InvalidName));
     }
 
-    let entries = match fs::read_dir(&target_dir)
-        .with_context(|| format!("failed to read target dir {}", target_dir.display()))
-    {
-        Ok(entries) => entries,
+    let record = match state.index.package(&input.name).await {
+        Ok(Some(record)) => record,
+        Ok(None) => return Err(Error(GetInfoError::PackageNotFound)),
         Err(e) => {
-            error!("internal failure: {e}");
+            error!("internal failure: {e:?}");
             return Err(Error(GetInfoError::InternalError));
         }
     };
 
-    let mut versions = entries
-        .filter_map(Result::ok)
-        .map(|e| e.file_name().to_string_lossy().to_string())
-        .collect::<Vec<_>>();
+    let releases: Vec<ReleaseInfo> = record
+        .sorted_versions()
+        .into_iter()
+        .filter_map(|(version, version_record)| {
+            let targets: Vec<_> = version_record
+                .targets
+                .iter()
+                .filter(|(triple, _)| input.triple.as_ref().is_none_or(|t| t == *triple))
+                .map(|(triple, target)| target.info(triple))
+                .collect();
+
+            (!targets.is_empty()).then(|| ReleaseInfo {
+                version: version.clone(),
+                yanked: version_record.yanked,
+                targets,
+            })
+        })
+        .collect();
 
-    versions.sort_by(sort_versions);
+    let versions: Vec<_> = releases
+        .iter()
+        .filter(|release| !release.yanked)
+        .map(|release| release.version.clone())
+        .collect();
 
-    if versions.is_empty() {
+    // callers that ask about a specific triple need a version to resolve, but
+    // a package whose versions are all yanked can still be inspected
+    let latest = match input.triple {
+        Some(_) => versions.last(),
+        None => versions.last().or(releases.last().map(|r| &r.version)),
+    };
+
+    let Some(latest) = latest else {
         return Err(Error(GetInfoError::PackageNotFound));
-    }
+    };
+
+    let metadata = record.versions[latest].metadata.clone();
 
     Ok(Output(GetInfoOutput {
         name: input.name,
         versions,
+        metadata,
+        releases,
     }))
 }
 
+/// Gets the index of a package.
+///
+/// The index is served as JSON lines with one entry per version.
+async fn get_index(
+    State(state): State<AppState>,
+    UrlPath(name): UrlPath<String>,
+) -> Result<Document, Error<GetIndexError>> {
+    info!("handling get index request");
+
+    if validate_name(&name).is_err() {
+        return Err(Error(GetIndexError::InvalidName));
+    }
+
+    let record = match state.index.package(&name).await {
+        Ok(Some(record)) => record,
+        Ok(None) => return Err(Error(GetIndexError::PackageNotFound)),
+        Err(e) => {
+            error!("internal failure: {e:?}");
+            return Err(Error(GetIndexError::InternalError));
+        }
+    };
+
+    let mut content = Vec::new();
+    for entry in record.index_entries() {
+        serde_json::to_writer(&mut content, &entry).expect("entry should serialize");
+        content.push(b'\n');
+    }
+
+    Ok(Document {
+        content_type: "application/x-ndjson",
+        content,
+    })
+}
+
+/// The number of packages that are listed per page by default.
+const DEFAULT_LIST_LIMIT: usize = 100;
+
+/// The maximum number of packages that can be listed per page.
+const MAX_LIST_LIMIT: usize = 1000;
+
+/// Lists packages in the registry.
+///
+/// Packages are listed in pages ordered by name, along with their latest
+/// version for the triple.
 async fn list(
     State(state): State<AppState>,
-    Json(input): Json<ListInput>,
+    Input(input): Input<ListInput>,
 ) -> Result<Output<ListOutput>, Error<ListError>> {
     info!("handling list request");
 
-    let registry = state.armory_home.join("registry");
-
-    let entries = match fs::read_dir(registry).context("failed to read registry") {
-        Ok(entries) => entries,
+    let names = match state.index.packages().await {
+        Ok(names) => names,
         Err(e) => {
-            error!("internal failure: {e}");
+            error!("internal failure: {e:?}");
             return Err(Error(ListError::InternalError));
         }
     };
 
+    let limit = input
+        .limit
+        .unwrap_or(DEFAULT_LIST_LIMIT)
+        .clamp(1, MAX_LIST_LIMIT);
+
+    let mut names = names
+        .into_iter()
+        .filter(|name| input.start_after.as_ref().is_none_or(|after| name > after))
+        .peekable();
+
     let mut packages = Vec::new();
-    for entry in entries {
-        let entry = match entry.context("failed to read registry entry") {
-            Ok(entry) => entry,
+    while packages.len() < limit {
+        let Some(name) = names.next() else {
+            break;
+        };
+
+        let record = match state.index.package(&name).await {
+            Ok(Some(record)) => record,
+            Ok(None) => continue,
             Err(e) => {
-                error!("internal failure: {e}");
+                error!("internal failure: {e:?}");
                 return Err(Error(ListError::InternalError));
             }
         };
 
-        let name = entry
-            .path()
-            .file_name()
-            .expect("name should exist")
-            .to_string_lossy()
-            .to_string();
-
-        let triple = entry.path().join(input.triple.to_string());
-        if !triple.is_dir() {
+        let Some(latest) = record.versions_for(&input.triple).pop() else {
             // no versions exist for the target triple
             continue;
+        };
+
+        let description = record.versions[&latest].metadata.description.clone();
+
+        packages.push(PackageSummary {
+            name,
+            version: latest,
+            description,
+        });
+    }
+
+    let next = match names.peek() {
+        Some(_) => packages.last().map(|package| package.name.clone()),
+        None => None,
+    };
+
+    Ok(Output(ListOutput { packages, next }))
+}
+
+/// Searches for packages in the registry.
+///
+/// Only packages that are available for the triple are considered. Results are
+/// ordered by how well they match, then by name.
+async fn search(
+    State(state): State<AppState>,
+    Input(input): Input<SearchInput>,
+) -> Result<Output<SearchOutput>, Error<SearchError>> {
+    info!("handling search request");
+
+    let names = match state.index.packages().await {
+        Ok(names) => names,
+        Err(e) => {
+            error!("internal failure: {e:?}");
+            return Err(Error(SearchError::InternalError));
+        }
+    };
+
+    let mut matches = Vec::new();
+    for name in names {
+        let record = match state.index.package(&name).await {
+            Ok(Some(record)) => record,
+            Ok(None) => continue,
+            Err(e) => {
+                error!("internal failure: {e:?}");
+                return Err(Error(SearchError::InternalError));
+            }
+        };
+
+        let Some(latest) = record.versions_for(&input.triple).pop() else {
+            continue;
+        };
+
+        let metadata = &record.versions[&latest].metadata;
+        let Some(score) = utils::search::score(
+            &input.query,
+            &name,
+            metadata.description.as_deref(),
+            &metadata.keywords,
+        ) else {
+            continue;
+        };
+
+        let result = PackageSummary {
+            description: metadata.description.clone(),
+            name,
+            version: latest,
+        };
+
+        matches.push((score, result));
+    }
+
+    // names are listed in order, so a stable sort keeps ties ordered by name
+    matches.sort_by(|(a, _), (b, _)| b.cmp(a));
+
+    Ok(Output(SearchOutput {
+        results: matches.into_iter().map(|(_, result)| result).collect(),
+    }))
+}
+
+/// Adds an owner to a package.
+///
+/// Only owners of the package and admins can add owners.
+async fn add_owner(
+    State(state): State<AppState>,
+    Extension(identity): Extension<Identity>,
+    Json(input): Json<AddOwnerInput>,
+) -> Result<Output<AddOwnerOutput>, Error<AddOwnerError>> {
+    info!("handling add owner request");
+
+    if validate_name(&input.name).is_err() {
+        return Err(Error(AddOwnerError::InvalidName));
+    }
+
+    let _guard = state.index.lock().await;
+
+    let mut record = match state.index.package(&input.name).await {
+        Ok(Some(record)) => record,
+        Ok(None) => return Err(Error(AddOwnerError::PackageNotFound)),
+        Err(e) => {
+            error!("internal failure: {e:?}");
+            return Err(Error(AddOwnerError::InternalError));
+        }
+    };
+
+    if !record.is_owner(&identity.user) && !identity.is_admin() {
+        return Err(Error(AddOwnerError::NotOwner));
+    }
+
+    if record.is_owner(&input.user) {
+        return Ok(Output(AddOwnerOutput {}));
+    }
+
+    record.owners.push(input.user.clone());
+
+    if let Err(e) = state.index.save(&record).await {
+        error!("internal failure: {e:?}");
+        return Err(Error(AddOwnerError::InternalError));
+    }
+
+    info!("added owner {} to package {}", input.user, input.name);
+
+    Ok(Output(AddOwnerOutput {}))
+}
+
+/// Removes an owner from a package.
+///
+/// Only owners of the package and admins can remove owners. The last owner of
+/// a package cannot be removed.
+async fn remove_owner(
+    State(state): State<AppState>,
+    Extension(identity): Extension<Identity>,
+    Json(input): Json<RemoveOwnerInput>,
+) -> Result<Output<RemoveOwnerOutput>, Error<RemoveOwnerError>> {
+    info!("handling remove owner request");
+
+    if validate_name(&input.name).is_err() {
+        return Err(Error(RemoveOwnerError::InvalidName));
+    }
+
+    let _guard = state.index.lock().await;
+
+    let mut record = match state.index.package(&input.name).await {
+        Ok(Some(record)) => record,
+        Ok(None) => return Err(Error(RemoveOwnerError::PackageNotFound)),
+        Err(e) => {
+            error!("internal failure: {e:?}");
+            return Err(Error(RemoveOwnerError::InternalError));
+        }
+    };
+
+    if !record.is_owner(&identity.user) && !identity.is_admin() {
+        return Err(Error(RemoveOwnerError::NotOwner));
+    }
+
+    let Some(pos) = record.owners.iter().position(|owner| *owner == input.user) else {
+        return Err(Error(RemoveOwnerError::OwnerNotFound));
+    };
+
+    if record.owners.len() == 1 {
+        return Err(Error(RemoveOwnerError::LastOwner));
+    }
+
+    record.owners.remove(pos);
+
+    if let Err(e) = state.index.save(&record).await {
+        error!("internal failure: {e:?}");
+        return Err(Error(RemoveOwnerError::InternalError));
+    }
+
+    info!("removed owner {} from package {}", input.user, input.name);
+
+    Ok(Output(RemoveOwnerOutput {}))
+}
+
+/// Lists the owners of a package.
+async fn list_owners(
+    State(state): State<AppState>,
+    Input(input): Input<ListOwnersInput>,
+) -> Result<Output<ListOwnersOutput>, Error<ListOwnersError>> {
+    info!("handling list owners request");
+
+    if validate_name(&input.name).is_err() {
+        return Err(Error(ListOwnersError::InvalidName));
+    }
+
+    let record = match state.index.package(&input.name).await {
+        Ok(Some(record)) => record,
+        Ok(None) => return Err(Error(ListOwnersError::PackageNotFound)),
+        Err(e) => {
+            error!("internal failure: {e:?}");
+            return Err(Error(ListOwnersError::InternalError));
+        }
+    };
+
+    Ok(Output(ListOwnersOutput {
+        owners: record.owners,
+    }))
+}
+
+/// Creates an API token.
+async fn create_token(
+    State(state): State<AppState>,
+    Json(input): Json<CreateTokenInput>,
+) -> Result<Output<CreateTokenOutput>, Error<CreateTokenError>> {
+    info!("handling create token request");
+
+    if input.scopes.is_empty() {
+        return Err(Error(CreateTokenError::ScopesMissing));
+    }
+
+    let (record, token) = match state.tokens.create(input.user, input.scopes).await {
+        Ok(created) => created,
+        Err(e) => {
+            error!("internal failure: {e:?}");
+            return Err(Error(CreateTokenError::InternalError));
         }
+    };
+
+    info!("created token {} for user {}", record.id, record.user);
+
+    Ok(Output(CreateTokenOutput {
+        info: record.into(),
+        token,
+    }))
+}
+
+/// Lists API tokens.
+async fn list_tokens(
+    State(state): State<AppState>,
+    Json(_input): Json<ListTokensInput>,
+) -> Result<Output<ListTokensOutput>, Error<ListTokensError>> {
+    info!("handling list tokens request");
 
-        packages.push(name);
+    let tokens = state
+        .tokens
+        .list()
+        .await
+        .into_iter()
+        .map(|record| record.into())
+        .collect();
+
+    Ok(Output(ListTokensOutput { tokens }))
+}
+
+/// Revokes an API token.
+async fn revoke_token(
+    State(state): State<AppState>,
+    Json(input): Json<RevokeTokenInput>,
+) -> Result<Output<RevokeTokenOutput>, Error<RevokeTokenError>> {
+    info!("handling revoke token request");
+
+    match state.tokens.revoke(&input.id).await {
+        Ok(true) => {}
+        Ok(false) => return Err(Error(RevokeTokenError::TokenNotFound)),
+        Err(e) => {
+            error!("internal failure: {e:?}");
+            return Err(Error(RevokeTokenError::InternalError));
+        }
     }
 
-    packages.sort();
+    info!("revoked token {}", input.id);
 
-    Ok(Output(ListOutput { packages }))
+    Ok(Output(RevokeTokenOutput {}))
 }
@@ -0,0 +1,147 @@
+use std::path::Path;
+
+use anyhow::Context;
+use model::{Format, Triple};
+use tracing::{info, warn};
+use utils::validation::{validate_name, validate_version};
+
+use crate::{
+    index::{Index, PackageRecord, TargetRecord, BLOBS, PACKAGES},
+    staging::stage,
+    storage::Storage,
+    tokens::TOKENS,
+};
+
+/// Migrates artifacts stored in the legacy layout into the index.
+///
+/// Before artifacts were stored as blobs, each one was stored at
+/// `{name}/{triple}/{version}`. Every artifact found in that layout is moved
+/// into a blob and recorded in the index, then deleted from its legacy
+/// location, so an interrupted migration picks up where it left off the next
+/// time it runs. Registries without legacy artifacts are left untouched.
+///
+/// Versions do not have to be semantic versions, since they were not required
+/// to be when the artifacts were published. Artifacts with a malformed name or
+/// version, which could not be looked up anyway, are skipped with a warning and
+/// left in the legacy layout.
+pub async fn migrate(storage: &dyn Storage, index: &Index, staging: &Path) -> anyhow::Result<()> {
+    let names = storage
+        .list("")
+        .await
+        .context("failed to list legacy packages")?;
+
+    for name in names {
+        if name == PACKAGES || name == BLOBS || name == TOKENS {
+            continue;
+        }
+
+        if let Err(e) = validate_name(&name) {
+            warn!("skipping legacy package {name}: {e}");
+            continue;
+        }
+
+        for triple in storage.list(&name).await? {
+            let Ok(parsed) = triple.parse::<Triple>() else {
+                warn!("skipping unrecognized triple {name}/{triple}");
+                continue;
+            };
+
+            for version in storage.list(&format!("{name}/{triple}")).await? {
+                let key = format!("{name}/{triple}/{version}");
+
+                if let Err(e) = validate_version(&version) {
+                    warn!("skipping legacy artifact {key}: {e}");
+                    continue;
+                }
+
+                let Some(object) = storage.get(&key).await? else {
+                    continue;
+                };
+
+                // legacy artifacts were accepted when they were published, so
+                // they are migrated regardless of the size limit
+                let staged = stage(staging, object.stream, u64::MAX)
+                    .await
+                    .with_context(|| format!("failed to stage {key}"))?;
+
+                let mut record = index
+                    .package(&name)
+                    .await?
+                    .unwrap_or_else(|| PackageRecord::new(name.clone()));
+
+                record.versions.entry(version).or_default().targets.insert(
+                    parsed.clone(),
+                    TargetRecord {
+                        digest: staged.digest.clone(),
+                        size: staged.size,
+                        compressed_size: Some(staged.compressed_size),
+                        signature: None,
+                        published_at: None,
+                        format: Format::Binary,
+                    },
+                );
+
+                index.put_blob(&staged.digest, staged.file).await?;
+                index.save(&record).await?;
+                storage.delete(&key).await?;
+
+                info!("migrated {key} to blob {}", staged.digest);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use model::Triple;
+
+    use super::migrate;
+    use crate::{
+        index::Index,
+        storage::{Filesystem, Storage},
+    };
+
+    #[tokio::test]
+    async fn legacy_versions() {
+        let home = tempfile::tempdir().unwrap();
+        let staging = home.path().join("staging");
+        std::fs::create_dir_all(&staging).unwrap();
+
+        let storage: Arc<dyn Storage> =
+            Arc::new(Filesystem::new(home.path().join("registry")).unwrap());
+        for key in [
+            "ripgrep/x86_64_linux/1.0.0",
+            "ripgrep/x86_64_linux/1.0",
+            "ripgrep/x86_64_linux/2",
+            "ripgrep/x86_64_linux/1.0 beta",
+            "RipGrep/x86_64_linux/1.0.0",
+        ] {
+            storage.write(key, key.as_bytes().to_vec()).await.unwrap();
+        }
+
+        let index = Index::new(storage.clone());
+        migrate(storage.as_ref(), &index, &staging).await.unwrap();
+
+        // versions that predate semantic versions are migrated too
+        let record = index.package("ripgrep").await.unwrap().unwrap();
+        let versions: Vec<_> = record.versions.keys().map(String::as_str).collect();
+        assert_eq!(versions, ["1.0", "1.0.0", "2"]);
+        for version in versions {
+            assert!(record.target(version, &Triple::X86_64Linux).is_some());
+            let key = format!("ripgrep/x86_64_linux/{version}");
+            assert!(!storage.exists(&key).await.unwrap());
+        }
+
+        // malformed entries are left where they are
+        assert!(storage
+            .exists("ripgrep/x86_64_linux/1.0 beta")
+            .await
+            .unwrap());
+        assert!(storage.exists("RipGrep/x86_64_linux/1.0.0").await.unwrap());
+        assert!(index.package("RipGrep").await.unwrap().is_none());
+    }
+}
@@ -0,0 +1,97 @@
+use std::{
+    error::Error,
+    fmt::{self, Display},
+    path::Path,
+};
+
+use anyhow::Context;
+use async_compression::tokio::write::ZstdEncoder;
+use bytes::Bytes;
+use futures_util::{Stream, StreamExt};
+use sha2::{Digest, Sha256};
+use tempfile::NamedTempFile;
+use tokio::io::AsyncWriteExt;
+
+/// An error that occurs when content is larger than the size limit.
+#[derive(Debug, Clone, Copy)]
+pub struct TooLarge {
+    /// The size limit in bytes.
+    pub limit: u64,
+}
+
+impl Display for TooLarge {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "content is larger than {} bytes", self.limit)
+    }
+}
+
+impl Error for TooLarge {}
+
+/// A file in the staging directory.
+pub struct Staged {
+    /// The staged file.
+    ///
+    /// The file holds the content compressed with zstd and is deleted when
+    /// dropped unless it is persisted.
+    pub file: NamedTempFile,
+    /// The SHA-256 digest of the content, encoded as lowercase hex.
+    pub digest: String,
+    /// The size of the content in bytes.
+    pub size: u64,
+    /// The size of the compressed content in bytes.
+    pub compressed_size: u64,
+}
+
+/// Streams content into a new file in the staging directory.
+///
+/// The content is hashed as it is written and compressed with zstd, so the
+/// digest and size describe the uncompressed content. Fails with [`TooLarge`]
+/// as soon as the content grows past `limit` bytes, so the limit holds for the
+/// decompressed size of compressed uploads too.
+pub async fn stage<S, E>(staging: &Path, mut stream: S, limit: u64) -> anyhow::Result<Staged>
+where
+    S: Stream<Item = Result<Bytes, E>> + Unpin,
+    E: std::error::Error + Send + Sync + 'static,
+{
+    let staged = NamedTempFile::new_in(staging).context("failed to create staging file")?;
+    let file = tokio::fs::File::from_std(
+        staged
+            .reopen()
+            .context("failed to open staging file for writing")?,
+    );
+    let mut encoder = ZstdEncoder::new(file);
+
+    let mut hasher = Sha256::new();
+    let mut size = 0;
+    while let Some(chunk) = stream.next().await {
+        let chunk = chunk.context("failed to read content")?;
+        hasher.update(&chunk);
+        size += chunk.len() as u64;
+        if size > limit {
+            return Err(TooLarge { limit }.into());
+        }
+        encoder
+            .write_all(&chunk)
+            .await
+            .context("failed to write to staging file")?;
+    }
+
+    encoder
+        .shutdown()
+        .await
+        .context("failed to flush staging file")?;
+
+    let compressed_size = encoder
+        .into_inner()
+        .metadata()
+        .await
+        .context("failed to read staging file metadata")?
+        .len();
+
+    Ok(Staged {
+        file: staged,
+        digest: format!("{:x}", hasher.finalize()),
+        size,
+        compressed_size,
+    })
+}
@@ -0,0 +1,156 @@
+use std::{
+    fs,
+    io::{self, SeekFrom, Write},
+    path::PathBuf,
+};
+
+use anyhow::{anyhow, Context};
+use async_trait::async_trait;
+use futures_util::StreamExt;
+use tempfile::NamedTempFile;
+use tokio::io::AsyncSeekExt;
+use tokio_util::io::ReaderStream;
+
+use super::{Object, Storage};
+
+/// Stores data on the local filesystem.
+///
+/// Keys map to paths relative to the root directory.
+pub struct Filesystem {
+    root: PathBuf,
+}
+
+impl Filesystem {
+    /// Creates a new filesystem backend rooted at a directory.
+    ///
+    /// The directory is created if it does not exist.
+    pub fn new(root: PathBuf) -> anyhow::Result<Self> {
+        fs::create_dir_all(&root)
+            .with_context(|| format!("failed to create storage root {}", root.display()))?;
+        Ok(Self { root })
+    }
+}
+
+#[async_trait]
+impl Storage for Filesystem {
+    async fn put(&self, key: &str, staged: NamedTempFile) -> anyhow::Result<()> {
+        let path = self.root.join(key);
+
+        tokio::fs::create_dir_all(path.parent().expect("path should have parent"))
+            .await
+            .with_context(|| format!("failed to create parent dir for {}", path.display()))?;
+
+        staged
+            .persist(&path)
+            .with_context(|| format!("failed to write object to {}", path.display()))?;
+
+        Ok(())
+    }
+
+    async fn write(&self, key: &str, content: Vec<u8>) -> anyhow::Result<()> {
+        let path = self.root.join(key);
+        let parent = path.parent().expect("path should have parent");
+
+        tokio::fs::create_dir_all(parent)
+            .await
+            .with_context(|| format!("failed to create parent dir for {}", path.display()))?;
+
+        // write to a temporary file first so that readers never observe a
+        // partially written object
+        let mut file = NamedTempFile::new_in(parent).context("failed to create temporary file")?;
+        file.write_all(&content)
+            .context("failed to write temporary file")?;
+        file.persist(&path)
+            .with_context(|| format!("failed to write object to {}", path.display()))?;
+
+        Ok(())
+    }
+
+    async fn get_from(&self, key: &str, offset: u64) -> anyhow::Result<Option<Object>> {
+        let path = self.root.join(key);
+
+        let metadata = match tokio::fs::metadata(&path).await {
+            Ok(metadata) => metadata,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => {
+                return Err(
+                    anyhow!(e).context(format!("failed to read metadata for {}", path.display()))
+                )
+            }
+        };
+
+        if !metadata.is_file() {
+            return Ok(None);
+        }
+
+        let mut file = tokio::fs::File::open(&path)
+            .await
+            .with_context(|| format!("failed to open {}", path.display()))?;
+
+        file.seek(SeekFrom::Start(offset))
+            .await
+            .with_context(|| format!("failed to seek in {}", path.display()))?;
+
+        Ok(Some(Object {
+            length: metadata.len(),
+            stream: ReaderStream::new(file).boxed(),
+        }))
+    }
+
+    async fn list(&self, prefix: &str) -> anyhow::Result<Vec<String>> {
+        let path = self.root.join(prefix);
+
+        let mut entries = match tokio::fs::read_dir(&path).await {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(vec![]),
+            Err(e) => return Err(anyhow!(e).context(format!("failed to read {}", path.display()))),
+        };
+
+        let mut names = Vec::new();
+        while let Some(entry) = entries
+            .next_entry()
+            .await
+            .with_context(|| format!("failed to read entry in {}", path.display()))?
+        {
+            names.push(entry.file_name().to_string_lossy().to_string());
+        }
+
+        Ok(names)
+    }
+
+    async fn exists(&self, key: &str) -> anyhow::Result<bool> {
+        let path = self.root.join(key);
+        match tokio::fs::metadata(&path).await {
+            Ok(metadata) => Ok(metadata.is_file()),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(false),
+            Err(e) => {
+                Err(anyhow!(e).context(format!("failed to read metadata for {}", path.display())))
+            }
+        }
+    }
+
+    async fn delete(&self, key: &str) -> anyhow::Result<()> {
+        let path = self.root.join(key);
+
+        match tokio::fs::remove_file(&path).await {
+            Ok(()) => {}
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(()),
+            Err(e) => {
+                return Err(anyhow!(e).context(format!("failed to delete {}", path.display())))
+            }
+        }
+
+        // prune directories left empty by the deletion so that listings match
+        // those of object stores, where prefixes only exist while they contain
+        // objects
+        let mut dir = path.parent();
+        while let Some(parent) = dir.filter(|dir| *dir != self.root) {
+            if tokio::fs::remove_dir(parent).await.is_err() {
+                break;
+            }
+            dir = parent.parent();
+        }
+
+        Ok(())
+    }
+}
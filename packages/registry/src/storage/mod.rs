@@ -0,0 +1,219 @@
+mod filesystem;
+mod s3;
+
+use std::{io, path::Path};
+
+use anyhow::Context;
+use async_trait::async_trait;
+use bytes::Bytes;
+use futures_util::{stream::BoxStream, TryStreamExt};
+use tempfile::NamedTempFile;
+
+pub use filesystem::Filesystem;
+pub use s3::S3;
+
+use crate::config::{Backend, Config};
+
+/// A stored object.
+pub struct Object {
+    /// The size of the object in bytes.
+    pub length: u64,
+    /// The content of the object, starting at the requested offset.
+    pub stream: BoxStream<'static, io::Result<Bytes>>,
+}
+
+/// A storage backend for registry data.
+///
+/// Objects are addressed by slash-separated keys such as
+/// `{name}/{triple}/{version}`. Each segment of a key behaves like a directory
+/// that can be listed to discover the keys beneath it.
+#[async_trait]
+pub trait Storage: Send + Sync {
+    /// Stores the content of a staged file under a key.
+    ///
+    /// Any existing object stored under the key is replaced.
+    async fn put(&self, key: &str, staged: NamedTempFile) -> anyhow::Result<()>;
+
+    /// Stores a small in-memory value under a key.
+    ///
+    /// Any existing object stored under the key is replaced.
+    async fn write(&self, key: &str, content: Vec<u8>) -> anyhow::Result<()>;
+
+    /// Loads the object stored under a key.
+    ///
+    /// Returns `None` if the object does not exist.
+    async fn get(&self, key: &str) -> anyhow::Result<Option<Object>> {
+        self.get_from(key, 0).await
+    }
+
+    /// Loads the content of the object stored under a key from an offset
+    /// onwards.
+    ///
+    /// The offset must be less than the size of the object unless it is zero.
+    /// Returns `None` if the object does not exist.
+    async fn get_from(&self, key: &str, offset: u64) -> anyhow::Result<Option<Object>>;
+
+    /// Loads the object stored under a key into memory.
+    ///
+    /// This should only be used for small objects. Returns `None` if the object
+    /// does not exist.
+    async fn read(&self, key: &str) -> anyhow::Result<Option<Vec<u8>>> {
+        let Some(object) = self.get(key).await? else {
+            return Ok(None);
+        };
+
+        let content = object
+            .stream
+            .try_fold(Vec::new(), |mut content, chunk| async move {
+                content.extend_from_slice(&chunk);
+                Ok(content)
+            })
+            .await
+            .with_context(|| format!("failed to read {key}"))?;
+
+        Ok(Some(content))
+    }
+
+    /// Checks whether an object is stored under a key.
+    async fn exists(&self, key: &str) -> anyhow::Result<bool>;
+
+    /// Deletes the object stored under a key.
+    ///
+    /// This method is idempotent and will not fail if the object does not
+    /// exist.
+    async fn delete(&self, key: &str) -> anyhow::Result<()>;
+
+    /// Lists the names of the segments directly beneath a prefix.
+    ///
+    /// An empty prefix lists the top level of the store. Returns an empty list
+    /// if nothing exists beneath the prefix.
+    async fn list(&self, prefix: &str) -> anyhow::Result<Vec<String>>;
+}
+
+/// Creates the storage backend selected in the registry configuration.
+///
+/// The filesystem backend stores data in the `registry` directory of the
+/// armory home directory.
+pub fn from_config(config: &Config, armory_home: &Path) -> anyhow::Result<Box<dyn Storage>> {
+    let storage: Box<dyn Storage> = match config.storage {
+        Backend::Filesystem => Box::new(Filesystem::new(armory_home.join("registry"))?),
+        Backend::S3 => Box::new(S3::new(&config.s3)?),
+    };
+    Ok(storage)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::Write,
+        time::{SystemTime, UNIX_EPOCH},
+    };
+
+    use confique::Config as _;
+    use futures_util::TryStreamExt;
+    use tempfile::NamedTempFile;
+
+    use super::{Filesystem, Storage, S3};
+    use crate::config::Config;
+
+    /// Creates a staged file with some content.
+    fn staged(content: &[u8]) -> NamedTempFile {
+        let mut file = NamedTempFile::new().unwrap();
+        file.write_all(content).unwrap();
+        file
+    }
+
+    /// Collects the content of an object.
+    async fn collect(storage: &dyn Storage, key: &str, offset: u64) -> Option<Vec<u8>> {
+        let object = storage.get_from(key, offset).await.unwrap()?;
+        let chunks = object.stream.try_collect::<Vec<_>>().await.unwrap();
+        Some(chunks.concat())
+    }
+
+    /// Checks that a backend upholds the contract of the [`Storage`] trait.
+    ///
+    /// All keys are created beneath a prefix, which must not exist yet.
+    async fn contract(storage: &dyn Storage, prefix: &str) {
+        let key = format!("{prefix}/ripgrep/x86_64_linux/1.0.0");
+
+        // missing objects
+        assert!(storage.get(&key).await.unwrap().is_none());
+        assert!(storage.read(&key).await.unwrap().is_none());
+        assert!(!storage.exists(&key).await.unwrap());
+        assert!(storage.list(prefix).await.unwrap().is_empty());
+        storage.delete(&key).await.unwrap();
+
+        // put and get
+        storage.put(&key, staged(b"hello world")).await.unwrap();
+        assert!(storage.exists(&key).await.unwrap());
+        let object = storage.get(&key).await.unwrap().unwrap();
+        assert_eq!(object.length, 11);
+        assert_eq!(collect(storage, &key, 0).await.unwrap(), b"hello world");
+
+        // get from an offset reports the full length
+        let object = storage.get_from(&key, 6).await.unwrap().unwrap();
+        assert_eq!(object.length, 11);
+        assert_eq!(collect(storage, &key, 6).await.unwrap(), b"world");
+
+        // put replaces existing objects
+        storage.put(&key, staged(b"goodbye")).await.unwrap();
+        assert_eq!(storage.read(&key).await.unwrap().unwrap(), b"goodbye");
+
+        // write replaces the whole object and leaves nothing else behind
+        let index = format!("{prefix}/ripgrep/index.json");
+        storage.write(&index, b"{}".to_vec()).await.unwrap();
+        storage.write(&index, b"[]".to_vec()).await.unwrap();
+        assert_eq!(storage.read(&index).await.unwrap().unwrap(), b"[]");
+
+        // list returns the segments directly beneath a prefix
+        let mut names = storage.list(&format!("{prefix}/ripgrep")).await.unwrap();
+        names.sort();
+        assert_eq!(names, ["index.json", "x86_64_linux"]);
+        assert_eq!(storage.list(prefix).await.unwrap(), ["ripgrep"]);
+        assert_eq!(
+            storage
+                .list(&format!("{prefix}/ripgrep/x86_64_linux"))
+                .await
+                .unwrap(),
+            ["1.0.0"]
+        );
+
+        // prefixes are not objects
+        assert!(!storage.exists(&format!("{prefix}/ripgrep")).await.unwrap());
+
+        // delete removes objects and the prefixes left empty
+        storage.delete(&key).await.unwrap();
+        assert!(!storage.exists(&key).await.unwrap());
+        assert!(storage.get(&key).await.unwrap().is_none());
+        assert_eq!(
+            storage.list(&format!("{prefix}/ripgrep")).await.unwrap(),
+            ["index.json"]
+        );
+
+        storage.delete(&index).await.unwrap();
+        assert!(storage.list(prefix).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn filesystem() {
+        let root = tempfile::tempdir().unwrap();
+        let storage = Filesystem::new(root.path().join("registry")).unwrap();
+        contract(&storage, "packages").await;
+    }
+
+    #[tokio::test]
+    #[ignore = "requires an S3-compatible store at ARMORY_S3_ENDPOINT"]
+    async fn s3() {
+        std::env::var("ARMORY_S3_ENDPOINT").expect("ARMORY_S3_ENDPOINT should be set");
+
+        let config = Config::builder().env().load().unwrap();
+        let storage = S3::new(&config.s3).unwrap();
+
+        // runs share the bucket, so each one works beneath its own prefix
+        let run = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_nanos();
+        contract(&storage, &format!("test-{run}")).await;
+    }
+}
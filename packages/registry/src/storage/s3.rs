@@ -0,0 +1,204 @@
+use anyhow::{bail, Context};
+use async_trait::async_trait;
+use futures_util::{StreamExt, TryStreamExt};
+use object_store::{
+    aws::{AmazonS3, AmazonS3Builder},
+    path::Path,
+    GetOptions, GetRange, MultipartUpload, ObjectStore, PutPayload,
+};
+use tempfile::NamedTempFile;
+use tokio::{fs::File, io::AsyncReadExt, task::JoinSet};
+
+use super::{Object, Storage};
+use crate::config::S3Config;
+
+/// The size of the chunks that staged files are uploaded in.
+const CHUNK_SIZE: usize = 8 * 1024 * 1024;
+
+/// The maximum number of chunks that are uploaded at once.
+const MAX_CONCURRENCY: usize = 4;
+
+/// Stores data in an S3-compatible object store.
+///
+/// Keys map directly to object keys in the bucket.
+pub struct S3 {
+    store: AmazonS3,
+}
+
+impl S3 {
+    /// Creates a new S3 backend.
+    ///
+    /// Settings that are not present in the config are sourced from the
+    /// standard `AWS_*` environment variables. Returns an error if no bucket is
+    /// configured.
+    pub fn new(config: &S3Config) -> anyhow::Result<Self> {
+        let Some(bucket) = config.bucket.as_ref() else {
+            bail!("no bucket configured for s3 storage; set ARMORY_S3_BUCKET");
+        };
+
+        let mut builder = AmazonS3Builder::from_env()
+            .with_bucket_name(bucket)
+            .with_allow_http(config.allow_http);
+
+        if let Some(region) = config.region.as_ref() {
+            builder = builder.with_region(region);
+        }
+
+        if let Some(endpoint) = config.endpoint.as_ref() {
+            builder = builder.with_endpoint(endpoint);
+        }
+
+        if let Some(access_key_id) = config.access_key_id.as_ref() {
+            builder = builder.with_access_key_id(access_key_id);
+        }
+
+        if let Some(secret_access_key) = config.secret_access_key.as_ref() {
+            builder = builder.with_secret_access_key(secret_access_key);
+        }
+
+        let store = builder.build().context("failed to configure s3 storage")?;
+
+        Ok(Self { store })
+    }
+}
+
+#[async_trait]
+impl Storage for S3 {
+    async fn put(&self, key: &str, staged: NamedTempFile) -> anyhow::Result<()> {
+        let mut file = File::from_std(
+            staged
+                .reopen()
+                .context("failed to open staged file for reading")?,
+        );
+
+        let mut upload = self
+            .store
+            .put_multipart(&Path::from(key))
+            .await
+            .with_context(|| format!("failed to start upload to {key}"))?;
+
+        if let Err(e) = upload_file(upload.as_mut(), &mut file).await {
+            // otherwise the parts that were already uploaded linger (and are
+            // billed) until the upload is cleaned up out of band
+            upload.abort().await.ok();
+            return Err(e).with_context(|| format!("failed to upload to {key}"));
+        }
+
+        Ok(())
+    }
+
+    async fn write(&self, key: &str, content: Vec<u8>) -> anyhow::Result<()> {
+        self.store
+            .put(&Path::from(key), PutPayload::from(content))
+            .await
+            .with_context(|| format!("failed to write {key}"))?;
+        Ok(())
+    }
+
+    async fn get_from(&self, key: &str, offset: u64) -> anyhow::Result<Option<Object>> {
+        // a range cannot be requested from an empty object, so the whole
+        // object is requested unless there is an offset
+        let options = GetOptions {
+            range: (offset > 0).then_some(GetRange::Offset(offset as usize)),
+            ..Default::default()
+        };
+
+        let result = match self.store.get_opts(&Path::from(key), options).await {
+            Ok(result) => result,
+            Err(object_store::Error::NotFound { .. }) => return Ok(None),
+            Err(e) => return Err(e).with_context(|| format!("failed to get {key}")),
+        };
+
+        Ok(Some(Object {
+            length: result.meta.size as u64,
+            stream: result.into_stream().map_err(std::io::Error::other).boxed(),
+        }))
+    }
+
+    async fn list(&self, prefix: &str) -> anyhow::Result<Vec<String>> {
+        let prefix = (!prefix.is_empty()).then(|| Path::from(prefix));
+
+        let result = self
+            .store
+            .list_with_delimiter(prefix.as_ref())
+            .await
+            .context("failed to list objects")?;
+
+        let names = result
+            .common_prefixes
+            .iter()
+            .chain(result.objects.iter().map(|object| &object.location))
+            .filter_map(|path| path.filename())
+            .map(|name| name.to_owned())
+            .collect();
+
+        Ok(names)
+    }
+
+    async fn exists(&self, key: &str) -> anyhow::Result<bool> {
+        match self.store.head(&Path::from(key)).await {
+            Ok(_) => Ok(true),
+            Err(object_store::Error::NotFound { .. }) => Ok(false),
+            Err(e) => Err(e).with_context(|| format!("failed to check {key}")),
+        }
+    }
+
+    async fn delete(&self, key: &str) -> anyhow::Result<()> {
+        match self.store.delete(&Path::from(key)).await {
+            Ok(()) | Err(object_store::Error::NotFound { .. }) => Ok(()),
+            Err(e) => Err(e).with_context(|| format!("failed to delete {key}")),
+        }
+    }
+}
+
+/// Uploads the contents of a file as the parts of a multipart upload, then
+/// completes the upload.
+///
+/// Up to [`MAX_CONCURRENCY`] parts are in flight at once. Parts that are still
+/// in flight when an error is returned are cancelled; the caller is
+/// responsible for aborting the upload.
+async fn upload_file(upload: &mut dyn MultipartUpload, file: &mut File) -> anyhow::Result<()> {
+    let mut parts = JoinSet::new();
+    loop {
+        let chunk = read_chunk(file)
+            .await
+            .context("failed to read staged file")?;
+
+        if chunk.is_empty() {
+            break;
+        }
+
+        while parts.len() >= MAX_CONCURRENCY {
+            if let Some(part) = parts.join_next().await {
+                part??;
+            }
+        }
+
+        let last = chunk.len() < CHUNK_SIZE;
+        parts.spawn(upload.put_part(PutPayload::from(chunk)));
+        if last {
+            break;
+        }
+    }
+
+    while let Some(part) = parts.join_next().await {
+        part??;
+    }
+
+    upload
+        .complete()
+        .await
+        .context("failed to complete upload")?;
+
+    Ok(())
+}
+
+/// Reads up to [`CHUNK_SIZE`] bytes from a file.
+///
+/// Only the final chunk of a file is shorter than [`CHUNK_SIZE`], because
+/// every part of a multipart upload except the last must meet a minimum size.
+async fn read_chunk(file: &mut File) -> std::io::Result<Vec<u8>> {
+    let mut chunk = Vec::with_capacity(CHUNK_SIZE);
+    file.take(CHUNK_SIZE as u64).read_to_end(&mut chunk).await?;
+    Ok(chunk)
+}
@@ -0,0 +1,224 @@
+use std::{
+    sync::Arc,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::Context;
+use model::{Scope, TokenInfo};
+use rand_core::{OsRng, RngCore};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use subtle::ConstantTimeEq;
+use tokio::sync::RwLock;
+
+use crate::storage::Storage;
+
+/// The storage key under which token records are stored.
+pub const TOKENS: &str = "tokens.json";
+
+/// The prefix of every token.
+///
+/// This makes tokens easy to recognize, e.g. by secret scanners.
+const TOKEN_PREFIX: &str = "armory_";
+
+/// The registry's API tokens.
+///
+/// Tokens are never stored. Only the SHA-256 hash of each token is kept, along
+/// with the user it belongs to and the scopes it grants. Records are kept in
+/// memory and written through to storage on every change.
+#[derive(Clone)]
+pub struct Tokens {
+    storage: Arc<dyn Storage>,
+    records: Arc<RwLock<Vec<TokenRecord>>>,
+}
+
+/// A record of an API token.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TokenRecord {
+    /// The identifier of the token.
+    pub id: String,
+    /// The user that the token belongs to.
+    pub user: String,
+    /// The scopes granted to the token.
+    pub scopes: Vec<Scope>,
+    /// When the token was created, in seconds since the Unix epoch.
+    pub created_at: u64,
+    /// The SHA-256 hash of the token, encoded as lowercase hex.
+    pub hash: String,
+}
+
+impl From<TokenRecord> for TokenInfo {
+    fn from(value: TokenRecord) -> Self {
+        TokenInfo {
+            id: value.id,
+            user: value.user,
+            scopes: value.scopes,
+            created_at: value.created_at,
+        }
+    }
+}
+
+impl Tokens {
+    /// Loads token records from storage.
+    pub async fn load(storage: Arc<dyn Storage>) -> anyhow::Result<Self> {
+        let records = match storage.read(TOKENS).await? {
+            Some(content) => serde_json::from_slice(&content).context("failed to parse tokens")?,
+            None => Vec::new(),
+        };
+
+        Ok(Self {
+            storage,
+            records: Arc::new(RwLock::new(records)),
+        })
+    }
+
+    /// Checks whether any tokens exist.
+    pub async fn is_empty(&self) -> bool {
+        self.records.read().await.is_empty()
+    }
+
+    /// Looks up the record of a token.
+    ///
+    /// Returns `None` if the token does not exist or has been revoked. Hashes
+    /// are compared in constant time, and every record is compared, so the
+    /// time taken does not reveal how much of a hash matched.
+    pub async fn authenticate(&self, token: &str) -> Option<TokenRecord> {
+        let hash = hash(token);
+        let mut found = None;
+        for record in self.records.read().await.iter() {
+            if bool::from(record.hash.as_bytes().ct_eq(hash.as_bytes())) {
+                found = Some(record.clone());
+            }
+        }
+        found
+    }
+
+    /// Lists all token records.
+    pub async fn list(&self) -> Vec<TokenRecord> {
+        self.records.read().await.clone()
+    }
+
+    /// Creates a new token.
+    ///
+    /// Returns the record of the token along with the token itself.
+    pub async fn create(
+        &self,
+        user: String,
+        scopes: Vec<Scope>,
+    ) -> anyhow::Result<(TokenRecord, String)> {
+        let mut records = self.records.write().await;
+
+        let token = format!("{TOKEN_PREFIX}{}", random_hex(32));
+
+        let id = loop {
+            let id = random_hex(4);
+            if !records.iter().any(|record| record.id == id) {
+                break id;
+            }
+        };
+
+        let created_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("time should be after epoch")
+            .as_secs();
+
+        let record = TokenRecord {
+            id,
+            user,
+            scopes,
+            created_at,
+            hash: hash(&token),
+        };
+
+        let mut updated = records.clone();
+        updated.push(record.clone());
+        self.save(&updated).await?;
+        *records = updated;
+
+        Ok((record, token))
+    }
+
+    /// Revokes a token.
+    ///
+    /// Returns `false` if the token does not exist.
+    pub async fn revoke(&self, id: &str) -> anyhow::Result<bool> {
+        let mut records = self.records.write().await;
+
+        let Some(pos) = records.iter().position(|record| record.id == id) else {
+            return Ok(false);
+        };
+
+        let mut updated = records.clone();
+        updated.remove(pos);
+        self.save(&updated).await?;
+        *records = updated;
+
+        Ok(true)
+    }
+
+    /// Writes token records to storage.
+    async fn save(&self, records: &[TokenRecord]) -> anyhow::Result<()> {
+        let content = serde_json::to_vec_pretty(records).context("failed to serialize tokens")?;
+        self.storage.write(TOKENS, content).await
+    }
+}
+
+/// Computes the SHA-256 hash of a token, encoded as lowercase hex.
+///
+/// Tokens are long random strings, so a fast hash is sufficient to protect
+/// them at rest.
+fn hash(token: &str) -> String {
+    format!("{:x}", Sha256::digest(token.as_bytes()))
+}
+
+/// Generates a random hex string from a number of random bytes.
+fn random_hex(bytes: usize) -> String {
+    let mut buffer = vec![0; bytes];
+    OsRng.fill_bytes(&mut buffer);
+    hex::encode(buffer)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use model::Scope;
+
+    use super::Tokens;
+    use crate::storage::Filesystem;
+
+    #[tokio::test]
+    async fn authenticate() {
+        let root = tempfile::tempdir().unwrap();
+        let storage = Arc::new(Filesystem::new(root.path().to_owned()).unwrap());
+        let tokens = Tokens::load(storage.clone()).await.unwrap();
+
+        let (alice, alice_token) = tokens
+            .create("alice".to_owned(), vec![Scope::Publish])
+            .await
+            .unwrap();
+        let (_, bob_token) = tokens
+            .create("bob".to_owned(), vec![Scope::Read])
+            .await
+            .unwrap();
+
+        let record = tokens.authenticate(&alice_token).await.unwrap();
+        assert_eq!(record.id, alice.id);
+        assert_eq!(record.user, "alice");
+        assert_eq!(tokens.authenticate(&bob_token).await.unwrap().user, "bob");
+
+        // a token that differs only in its last character is rejected
+        let mut forged = alice_token.clone();
+        forged.pop();
+        forged.push('x');
+        assert!(tokens.authenticate(&forged).await.is_none());
+        assert!(tokens.authenticate("").await.is_none());
+
+        // tokens survive a reload, and revoked tokens are rejected
+        let tokens = Tokens::load(storage).await.unwrap();
+        assert!(tokens.authenticate(&alice_token).await.is_some());
+        assert!(tokens.revoke(&alice.id).await.unwrap());
+        assert!(tokens.authenticate(&alice_token).await.is_none());
+        assert!(tokens.authenticate(&bob_token).await.is_some());
+    }
+}
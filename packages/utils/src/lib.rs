@@ -1,28 +1,18 @@
 use std::cmp::Ordering;
 
-/// Sorts version strings in ascending order.
-pub fn sort_versions(a: &String, b: &String) -> Ordering {
-    let mut a = a.split(".");
-    let mut b = b.split(".");
-    loop {
-        match (a.next(), b.next()) {
-            (None, None) => return Ordering::Equal,
-            (None, Some(_b)) => return Ordering::Less,
-            (Some(_a), None) => return Ordering::Greater,
-            (Some(a), Some(b)) => {
-                let a = a.parse::<u8>().expect("version part should be integer");
-                let b = b.parse::<u8>().expect("version part should be integer");
+pub mod requirement;
+pub mod search;
+pub mod validation;
+pub mod version;
 
-                if a < b {
-                    return Ordering::Less;
-                }
+pub use requirement::VersionReq;
+pub use version::Version;
 
-                if a > b {
-                    return Ordering::Greater;
-                }
-            }
-        }
-    }
+/// Sorts version strings in ascending order.
+///
+/// See [`Version`] for how versions are ordered.
+pub fn sort_versions(a: &str, b: &str) -> Ordering {
+    Version::parse(a).cmp(&Version::parse(b))
 }
 
 #[cfg(test)]
@@ -40,7 +30,7 @@ mod tests {
             String::from("2"),
         ];
 
-        versions.sort_by(sort_versions);
+        versions.sort_by(|a, b| sort_versions(a, b));
 
         assert_eq!(
             versions,
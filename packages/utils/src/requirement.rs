@@ -0,0 +1,422 @@
+use std::{
+    error::Error,
+    fmt::{self, Display},
+    str::FromStr,
+};
+
+use crate::Version;
+
+/// A requirement on the version of a package.
+///
+/// A requirement is one or more comparators separated by commas, all of which
+/// a version must satisfy:
+///
+/// - `1.2.3` or `=1.2.3` matches exactly that version.
+/// - `1.2`, `1`, `1.2.*`, and `1.*` match any version that starts with the
+///   given components, and `*` matches any version.
+/// - `^1.2.3` matches versions that are compatible with `1.2.3`, from `1.2.3`
+///   up to but not including `2.0.0`. The leftmost nonzero component is kept,
+///   so `^0.2.3` stops short of `0.3.0`.
+/// - `~1.2.3` matches patch updates, from `1.2.3` up to but not including
+///   `1.3.0`. `~1` matches minor updates as well.
+/// - `>`, `>=`, `<`, and `<=` compare against a version, with missing
+///   components filled in the way that makes sense for the operator, so `>1`
+///   starts at `2.0.0` and `<=1.2` matches every `1.2.x` version.
+///
+/// Pre-release versions only match if one of the comparators names a
+/// pre-release of the same `MAJOR.MINOR.PATCH`, so that a range never picks up
+/// a pre-release unless asked to. `>=1.0.0-rc.1` matches `1.0.0-rc.2` but not
+/// `1.1.0-beta`.
+///
+/// Versions with fewer than three components, such as `1.0`, are matched as if
+/// the missing components were zero, so `1` and `=1.0.0` both match `1.0`.
+#[derive(Debug, Clone)]
+pub struct VersionReq {
+    text: String,
+    comparators: Vec<Comparator>,
+}
+
+/// A single condition within a requirement.
+#[derive(Debug, Clone)]
+struct Comparator {
+    op: Op,
+    major: u64,
+    minor: Option<u64>,
+    patch: Option<u64>,
+    pre: Option<String>,
+}
+
+/// The operator of a comparator.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    Exact,
+    Greater,
+    GreaterEq,
+    Less,
+    LessEq,
+    Tilde,
+    Caret,
+}
+
+/// An error that occurs when parsing a version requirement.
+#[derive(Debug, Clone)]
+pub struct ParseReqError {
+    requirement: String,
+    reason: &'static str,
+}
+
+impl Display for ParseReqError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "invalid version requirement '{}': {}",
+            self.requirement, self.reason
+        )
+    }
+}
+
+impl Error for ParseReqError {}
+
+impl VersionReq {
+    /// Parses a version requirement.
+    pub fn parse(s: &str) -> Result<Self, ParseReqError> {
+        let error = |reason| ParseReqError {
+            requirement: s.to_owned(),
+            reason,
+        };
+
+        let text = s.trim();
+        if text.is_empty() {
+            return Err(error("requirement is empty"));
+        }
+
+        let mut comparators = Vec::new();
+        for part in text.split(',') {
+            let part = part.trim();
+            if part.is_empty() {
+                return Err(error("comparator is empty"));
+            }
+
+            if matches!(part, "*" | "x" | "X") {
+                continue;
+            }
+
+            comparators.push(Comparator::parse(part).map_err(error)?);
+        }
+
+        Ok(Self {
+            text: text.to_owned(),
+            comparators,
+        })
+    }
+
+    /// Checks whether a version satisfies the requirement.
+    pub fn matches(&self, version: &Version) -> bool {
+        let version = &version.padded();
+        if !self.comparators.iter().all(|c| c.matches(version)) {
+            return false;
+        }
+
+        !version.is_prerelease()
+            || self
+                .comparators
+                .iter()
+                .any(|c| c.pre.is_some() && c.lower().same_release(version))
+    }
+
+    /// Checks whether the requirement matches a single version, such as
+    /// `1.2.3` or `=1.2.3`.
+    pub fn is_exact(&self) -> bool {
+        matches!(
+            self.comparators.as_slice(),
+            [Comparator {
+                op: Op::Exact,
+                patch: Some(_),
+                ..
+            }]
+        )
+    }
+
+    /// Returns the highest of the versions that satisfy the requirement, if
+    /// any.
+    pub fn best_match<'a, I>(&self, versions: I) -> Option<&'a str>
+    where
+        I: IntoIterator<Item = &'a str>,
+    {
+        versions
+            .into_iter()
+            .map(|version| (version, Version::parse(version)))
+            .filter(|(_, parsed)| self.matches(parsed))
+            .max_by(|(_, a), (_, b)| a.cmp(b))
+            .map(|(version, _)| version)
+    }
+}
+
+impl Comparator {
+    /// Parses a comparator such as `>=1.2` or `^0.3.1-rc.1`.
+    fn parse(s: &str) -> Result<Self, &'static str> {
+        let (op, rest) = [
+            (">=", Op::GreaterEq),
+            ("<=", Op::LessEq),
+            (">", Op::Greater),
+            ("<", Op::Less),
+            ("=", Op::Exact),
+            ("~", Op::Tilde),
+            ("^", Op::Caret),
+        ]
+        .into_iter()
+        .find_map(|(prefix, op)| s.strip_prefix(prefix).map(|rest| (op, rest)))
+        .unwrap_or((Op::Exact, s));
+
+        // build metadata does not affect precedence, so it is ignored
+        let rest = rest.trim_start();
+        let rest = rest.split_once('+').map_or(rest, |(rest, _)| rest);
+
+        let (release, pre) = match rest.split_once('-') {
+            Some((release, pre)) => (release, Some(pre)),
+            None => (rest, None),
+        };
+
+        let mut components = Vec::new();
+        let mut wildcard = false;
+        for component in release.split('.') {
+            match component {
+                "*" | "x" | "X" => wildcard = true,
+                _ if wildcard => return Err("wildcards must come last"),
+                "" => return Err("version component is empty"),
+                _ if !component.bytes().all(|b| b.is_ascii_digit()) => {
+                    return Err("version components must be numbers")
+                }
+                _ => components.push(
+                    component
+                        .parse()
+                        .map_err(|_| "version component is too large")?,
+                ),
+            }
+        }
+
+        let (major, minor, patch) = match components.as_slice() {
+            [] => return Err("a wildcard must follow a version component"),
+            [major] => (*major, None, None),
+            [major, minor] => (*major, Some(*minor), None),
+            [major, minor, patch] => (*major, Some(*minor), Some(*patch)),
+            _ => return Err("versions have at most three components"),
+        };
+
+        if let Some(pre) = pre {
+            if patch.is_none() {
+                return Err("a pre-release requires a full version");
+            }
+            if pre.split('.').any(str::is_empty) {
+                return Err("pre-release identifier is empty");
+            }
+        }
+
+        Ok(Self {
+            op,
+            major,
+            minor,
+            patch,
+            pre: pre.map(str::to_owned),
+        })
+    }
+
+    /// Checks whether a version satisfies the comparator, ignoring whether it
+    /// is a pre-release.
+    fn matches(&self, version: &Version) -> bool {
+        let lower = self.lower();
+        let at_least = |bound: &Version| version.precedence(bound).is_ge();
+        let below = |bound: &Version| version.precedence(bound).is_lt();
+        let partial = self.patch.is_none();
+
+        match self.op {
+            Op::Exact if partial => at_least(&lower) && below(&self.next()),
+            Op::Exact => version.precedence(&lower).is_eq(),
+            Op::Greater if partial => at_least(&self.next()),
+            Op::Greater => version.precedence(&lower).is_gt(),
+            Op::GreaterEq => at_least(&lower),
+            Op::Less => below(&lower),
+            Op::LessEq if partial => below(&self.next()),
+            Op::LessEq => version.precedence(&lower).is_le(),
+            Op::Tilde => {
+                let upper = match self.minor {
+                    Some(minor) => release(self.major, minor.saturating_add(1), 0),
+                    None => release(self.major.saturating_add(1), 0, 0),
+                };
+                at_least(&lower) && below(&upper)
+            }
+            Op::Caret => {
+                let upper = match (self.major, self.minor, self.patch) {
+                    (0, Some(0), Some(patch)) => release(0, 0, patch.saturating_add(1)),
+                    (0, Some(minor), _) => release(0, minor.saturating_add(1), 0),
+                    (major, _, _) => release(major.saturating_add(1), 0, 0),
+                };
+                at_least(&lower) && below(&upper)
+            }
+        }
+    }
+
+    /// Returns the lowest version that the comparator names, with missing
+    /// components set to zero.
+    fn lower(&self) -> Version {
+        let mut text = format!(
+            "{}.{}.{}",
+            self.major,
+            self.minor.unwrap_or(0),
+            self.patch.unwrap_or(0)
+        );
+        if let Some(pre) = &self.pre {
+            text = format!("{text}-{pre}");
+        }
+        Version::parse(&text)
+    }
+
+    /// Returns the first version after every version that starts with the
+    /// components that the comparator names.
+    fn next(&self) -> Version {
+        match (self.minor, self.patch) {
+            (None, _) => release(self.major.saturating_add(1), 0, 0),
+            (Some(minor), None) => release(self.major, minor.saturating_add(1), 0),
+            (Some(minor), Some(patch)) => release(self.major, minor, patch.saturating_add(1)),
+        }
+    }
+}
+
+/// Returns a release version.
+fn release(major: u64, minor: u64, patch: u64) -> Version {
+    Version::parse(&format!("{major}.{minor}.{patch}"))
+}
+
+impl FromStr for VersionReq {
+    type Err = ParseReqError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        VersionReq::parse(s)
+    }
+}
+
+impl Display for VersionReq {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::VersionReq;
+    use crate::Version;
+
+    /// Asserts which of the versions satisfy a requirement.
+    fn assert_matches(requirement: &str, matching: &[&str], other: &[&str]) {
+        let req = VersionReq::parse(requirement).unwrap();
+        for version in matching {
+            assert!(
+                req.matches(&Version::parse(version)),
+                "{version} should match {requirement}"
+            );
+        }
+        for version in other {
+            assert!(
+                !req.matches(&Version::parse(version)),
+                "{version} should not match {requirement}"
+            );
+        }
+    }
+
+    #[test]
+    fn exact() {
+        assert_matches("1.2.3", &["1.2.3", "1.2.3+build"], &["1.2.4", "1.2.3-rc.1"]);
+        assert_matches("=1.2.3", &["1.2.3"], &["1.2.2"]);
+        assert_matches("1.0.0-rc.1", &["1.0.0-rc.1"], &["1.0.0", "1.0.0-rc.2"]);
+        assert!(VersionReq::parse("1.2.3").unwrap().is_exact());
+        assert!(!VersionReq::parse("1.2").unwrap().is_exact());
+    }
+
+    #[test]
+    fn partial() {
+        assert_matches("1", &["1.0.0", "1.9.3"], &["0.9.0", "2.0.0"]);
+        assert_matches("1.2", &["1.2.0", "1.2.9"], &["1.1.9", "1.3.0"]);
+        assert_matches("1.*", &["1.0.0", "1.5.0"], &["2.0.0"]);
+        assert_matches("*", &["0.0.1", "3.0.0"], &["3.0.0-beta"]);
+    }
+
+    #[test]
+    fn caret() {
+        assert_matches(
+            "^1.2",
+            &["1.2.0", "1.9.0"],
+            &["1.1.0", "2.0.0", "2.0.0-alpha"],
+        );
+        assert_matches("^1.2.3", &["1.2.3", "1.3.0"], &["1.2.2", "2.0.0"]);
+        assert_matches("^0.2.3", &["0.2.3", "0.2.9"], &["0.3.0"]);
+        assert_matches("^0.0.3", &["0.0.3"], &["0.0.4"]);
+        assert_matches("^0", &["0.0.1", "0.9.0"], &["1.0.0"]);
+    }
+
+    #[test]
+    fn tilde() {
+        assert_matches("~1.4", &["1.4.0", "1.4.7"], &["1.3.9", "1.5.0"]);
+        assert_matches("~1.4.2", &["1.4.2", "1.4.7"], &["1.4.1", "1.5.0"]);
+        assert_matches("~1", &["1.0.0", "1.9.0"], &["2.0.0"]);
+    }
+
+    #[test]
+    fn comparisons() {
+        assert_matches(
+            ">=2, <3",
+            &["2.0.0", "2.9.9"],
+            &["1.9.9", "3.0.0", "3.0.0-rc.1"],
+        );
+        assert_matches(">1", &["2.0.0"], &["1.9.9"]);
+        assert_matches(">1.2.3", &["1.2.4"], &["1.2.3"]);
+        assert_matches("<=1.2", &["1.2.9"], &["1.3.0"]);
+        assert_matches("<1.2", &["1.1.9"], &["1.2.0"]);
+        assert_matches(">= 1.0.0", &["1.0.0"], &["0.9.0"]);
+    }
+
+    #[test]
+    fn prerelease() {
+        assert_matches(
+            ">=1.0.0-rc.1",
+            &["1.0.0-rc.1", "1.0.0-rc.2", "1.0.0", "1.1.0"],
+            &["1.0.0-beta", "1.1.0-beta"],
+        );
+    }
+
+    #[test]
+    fn lenient() {
+        assert_matches("1", &["1.0", "1", "1.5"], &["0.9", "2"]);
+        assert_matches("=1.0.0", &["1.0", "1"], &["1.0.1", "1.1"]);
+        assert_matches("^1.2", &["1.2", "1.10"], &["1.1", "2"]);
+        assert_matches("<2", &["1.9"], &["2", "2.0"]);
+        assert_matches(
+            ">=1.0.0-rc.1",
+            &["1.0-rc.2", "1.1"],
+            &["1.0-beta", "1.1-beta"],
+        );
+        assert_matches("2024", &["2024.10.3", "2024.1"], &["2025.1"]);
+    }
+
+    #[test]
+    fn best_match() {
+        let req = VersionReq::parse("^1.2").unwrap();
+        let versions = ["1.1.0", "1.2.0", "1.10.0", "1.9.0", "2.0.0"];
+        assert_eq!(req.best_match(versions), Some("1.10.0"));
+
+        let req = VersionReq::parse("^3").unwrap();
+        assert_eq!(req.best_match(versions), None);
+
+        let req = VersionReq::parse("1").unwrap();
+        assert_eq!(req.best_match(["0.9", "1.0", "1.2", "2.0"]), Some("1.2"));
+    }
+
+    #[test]
+    fn invalid() {
+        for invalid in ["", "1.2.3.4", "1.a", ">=", "1, ", "*.1", "1.2-rc", "latest"] {
+            assert!(
+                VersionReq::parse(invalid).is_err(),
+                "{invalid:?} should be invalid"
+            );
+        }
+    }
+}
@@ -0,0 +1,162 @@
+/// Scores how well a package matches a search query.
+///
+/// Matching is case-insensitive. The name is matched exactly, by prefix, by
+/// substring, and fuzzily, while keywords and the description are matched by
+/// substring. Returns `None` if the package does not match at all, or a score
+/// where higher is better otherwise.
+pub fn score(
+    query: &str,
+    name: &str,
+    description: Option<&str>,
+    keywords: &[String],
+) -> Option<u32> {
+    let query = query.trim().to_lowercase();
+    if query.is_empty() {
+        return None;
+    }
+
+    let name = name.to_lowercase();
+    let keywords: Vec<_> = keywords.iter().map(|k| k.to_lowercase()).collect();
+    let description = description.unwrap_or_default().to_lowercase();
+
+    let score = if name == query {
+        100
+    } else if name.starts_with(&query) {
+        80
+    } else if name.contains(&query) {
+        60
+    } else if keywords.contains(&query) {
+        50
+    } else if keywords.iter().any(|keyword| keyword.contains(&query)) {
+        40
+    } else if description.contains(&query) {
+        30
+    } else if is_typo(&query, &name) {
+        20
+    } else if is_subsequence(&query, &name) {
+        10
+    } else {
+        return None;
+    };
+
+    Some(score)
+}
+
+/// Checks whether a query is a misspelling of a name.
+///
+/// Longer queries tolerate more edits.
+fn is_typo(query: &str, name: &str) -> bool {
+    let max_distance = match query.chars().count() {
+        0..=3 => 0,
+        4..=7 => 1,
+        _ => 2,
+    };
+    max_distance > 0 && distance(query, name) <= max_distance
+}
+
+/// Checks whether the characters of a query appear in a name in order.
+fn is_subsequence(query: &str, name: &str) -> bool {
+    let mut chars = name.chars();
+    query.chars().all(|c| chars.any(|n| n == c))
+}
+
+/// Returns the Levenshtein distance between two strings.
+fn distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        previous = current;
+    }
+
+    previous[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{distance, is_typo, score};
+
+    /// Scores a package without a description or keywords.
+    fn name_score(query: &str, name: &str) -> Option<u32> {
+        score(query, name, None, &[])
+    }
+
+    #[test]
+    fn ranking() {
+        let keywords = ["grep".to_owned(), "search".to_owned()];
+        let exact = score("grep", "grep", None, &[]);
+        let prefix = score("grep", "grepper", None, &[]);
+        let substring = score("grep", "ripgrep", None, &[]);
+        let keyword = score("grep", "rg", None, &keywords);
+        let partial_keyword = score("sea", "rg", None, &keywords);
+        let description = score("grep", "rg", Some("a faster grep"), &[]);
+
+        assert!(exact > prefix);
+        assert!(prefix > substring);
+        assert!(substring > keyword);
+        assert!(keyword > partial_keyword);
+        assert!(partial_keyword > description);
+        assert!(description.is_some());
+    }
+
+    #[test]
+    fn fuzzy_matches_rank_last() {
+        let description = score("finder", "fd", Some("a simple file finder"), &[]);
+        let typo = name_score("ripgrap", "ripgrep");
+        let subsequence = name_score("rgp", "ripgrep");
+
+        assert!(description > typo);
+        assert!(typo > subsequence);
+        assert!(subsequence.is_some());
+    }
+
+    #[test]
+    fn case_insensitive() {
+        assert_eq!(
+            name_score("RipGrep", "ripgrep"),
+            name_score("ripgrep", "ripgrep")
+        );
+        assert_eq!(
+            score("GREP", "rg", None, &["Grep".to_owned()]),
+            score("grep", "rg", None, &["grep".to_owned()])
+        );
+    }
+
+    #[test]
+    fn no_match() {
+        assert_eq!(name_score("", "ripgrep"), None);
+        assert_eq!(name_score("   ", "ripgrep"), None);
+        assert_eq!(name_score("bat", "ripgrep"), None);
+    }
+
+    #[test]
+    fn typo_threshold() {
+        // queries of up to three characters must match exactly
+        assert!(!is_typo("fdd", "fd"));
+        assert!(!is_typo("bta", "bat"));
+
+        // queries of four to seven characters tolerate one edit
+        assert!(is_typo("ripgre", "ripgrep"));
+        assert!(is_typo("ripgrap", "ripgrep"));
+        assert!(!is_typo("rpgrap", "ripgrep"));
+
+        // longer queries tolerate two edits
+        assert!(is_typo("hyperfin", "hyperfine"));
+        assert!(is_typo("hyprefine", "hyperfine"));
+        assert!(!is_typo("hyprefone", "hyperfine"));
+    }
+
+    #[test]
+    fn levenshtein() {
+        assert_eq!(distance("", ""), 0);
+        assert_eq!(distance("", "abc"), 3);
+        assert_eq!(distance("kitten", "sitting"), 3);
+        assert_eq!(distance("ripgrep", "ripgrep"), 0);
+        assert_eq!(distance("ripgrap", "ripgrep"), 1);
+    }
+}
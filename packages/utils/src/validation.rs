@@ -0,0 +1,243 @@
+use std::{
+    error::Error,
+    fmt::{self, Display},
+};
+
+use crate::{Version, VersionReq};
+
+/// The maximum length of a package name.
+pub const MAX_NAME_LENGTH: usize = 64;
+
+/// The maximum length of a version.
+pub const MAX_VERSION_LENGTH: usize = 128;
+
+/// The maximum length of a tag.
+pub const MAX_TAG_LENGTH: usize = 32;
+
+/// Names that only admins can publish packages under.
+///
+/// Armory itself is published as `armory`, and the CLI uses `self` to refer to
+/// it, so neither can be claimed by anyone else.
+pub const RESERVED_NAMES: &[&str] = &["armory", "self"];
+
+/// An error that occurs when a package name or version breaks the rules.
+#[derive(Debug, Clone)]
+pub struct ValidationError {
+    subject: &'static str,
+    value: String,
+    reason: &'static str,
+}
+
+impl Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "invalid {} '{}': {}",
+            self.subject, self.value, self.reason
+        )
+    }
+}
+
+impl Error for ValidationError {}
+
+/// Checks whether a package name is valid.
+///
+/// Names are between 1 and 64 characters long and consist of lowercase ASCII
+/// letters, digits, `-`, and `_`, starting with a letter or digit. Names are
+/// used as file names on both the registry and clients, so this keeps them
+/// portable and unambiguous on case-insensitive file systems.
+pub fn validate_name(name: &str) -> Result<(), ValidationError> {
+    let error = |reason| ValidationError {
+        subject: "package name",
+        value: name.to_owned(),
+        reason,
+    };
+
+    if name.is_empty() {
+        return Err(error("name is empty"));
+    }
+
+    if name.len() > MAX_NAME_LENGTH {
+        return Err(error("name is longer than 64 characters"));
+    }
+
+    if !name
+        .bytes()
+        .all(|b| b.is_ascii_lowercase() || b.is_ascii_digit() || b == b'-' || b == b'_')
+    {
+        return Err(error(
+            "name may only contain lowercase letters, digits, '-', and '_'",
+        ));
+    }
+
+    if !name.as_bytes()[0].is_ascii_alphanumeric() {
+        return Err(error("name must start with a letter or digit"));
+    }
+
+    Ok(())
+}
+
+/// Checks whether a package name is reserved.
+pub fn is_reserved(name: &str) -> bool {
+    RESERVED_NAMES.contains(&name)
+}
+
+/// Checks whether a version is well formed.
+///
+/// Versions are between 1 and 128 characters long and consist of ASCII letters,
+/// digits, `.`, `-`, and `+`. This is looser than [`validate_semver`] so that
+/// versions published before semantic versions were required can still be
+/// looked up.
+pub fn validate_version(version: &str) -> Result<(), ValidationError> {
+    let error = |reason| ValidationError {
+        subject: "version",
+        value: version.to_owned(),
+        reason,
+    };
+
+    if version.is_empty() {
+        return Err(error("version is empty"));
+    }
+
+    if version.len() > MAX_VERSION_LENGTH {
+        return Err(error("version is longer than 128 characters"));
+    }
+
+    if !version
+        .bytes()
+        .all(|b| b.is_ascii_alphanumeric() || matches!(b, b'.' | b'-' | b'+'))
+    {
+        return Err(error(
+            "version may only contain letters, digits, '.', '-', and '+'",
+        ));
+    }
+
+    Ok(())
+}
+
+/// Checks whether a version is a valid semantic version, as is required of new
+/// versions.
+pub fn validate_semver(version: &str) -> Result<(), ValidationError> {
+    validate_version(version)?;
+
+    if !Version::parse(version).is_semver() {
+        return Err(ValidationError {
+            subject: "version",
+            value: version.to_owned(),
+            reason: "version must be a semantic version such as 1.2.3",
+        });
+    }
+
+    Ok(())
+}
+
+/// Checks whether a tag is valid.
+///
+/// Tags are between 1 and 32 characters long and consist of lowercase ASCII
+/// letters, digits, `-`, and `_`, starting with a letter. Tags are given in
+/// place of versions, so a tag cannot also be read as a version requirement.
+pub fn validate_tag(tag: &str) -> Result<(), ValidationError> {
+    let error = |reason| ValidationError {
+        subject: "tag",
+        value: tag.to_owned(),
+        reason,
+    };
+
+    if tag.is_empty() {
+        return Err(error("tag is empty"));
+    }
+
+    if tag.len() > MAX_TAG_LENGTH {
+        return Err(error("tag is longer than 32 characters"));
+    }
+
+    if !tag
+        .bytes()
+        .all(|b| b.is_ascii_lowercase() || b.is_ascii_digit() || b == b'-' || b == b'_')
+    {
+        return Err(error(
+            "tag may only contain lowercase letters, digits, '-', and '_'",
+        ));
+    }
+
+    if !tag.as_bytes()[0].is_ascii_lowercase() {
+        return Err(error("tag must start with a letter"));
+    }
+
+    if VersionReq::parse(tag).is_ok() {
+        return Err(error("tag cannot be a version requirement"));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{is_reserved, validate_name, validate_semver, validate_tag, validate_version};
+
+    #[test]
+    fn names() {
+        for valid in ["ripgrep", "fd", "7zip", "cargo-edit", "my_tool"] {
+            assert!(validate_name(valid).is_ok(), "{valid} should be valid");
+        }
+
+        let too_long = "a".repeat(65);
+        for invalid in [
+            "",
+            "..",
+            "../etc",
+            "a/b",
+            "a\\b",
+            "-tool",
+            "_tool",
+            "Tool",
+            "tool.exe",
+            "tool name",
+            too_long.as_str(),
+        ] {
+            assert!(
+                validate_name(invalid).is_err(),
+                "{invalid} should be invalid"
+            );
+        }
+    }
+
+    #[test]
+    fn reserved_names() {
+        assert!(is_reserved("armory"));
+        assert!(is_reserved("self"));
+        assert!(!is_reserved("ripgrep"));
+    }
+
+    #[test]
+    fn versions() {
+        for valid in ["1.0.0", "1.0", "2024.10.3", "1.0.0-rc.1+build"] {
+            assert!(validate_version(valid).is_ok(), "{valid} should be valid");
+        }
+
+        for invalid in ["", "../1.0.0", "1.0.0/x", "1.0 beta"] {
+            assert!(
+                validate_version(invalid).is_err(),
+                "{invalid} should be invalid"
+            );
+        }
+
+        assert!(validate_semver("1.0.0-rc.1").is_ok());
+        assert!(validate_semver("1.0").is_err());
+        assert!(validate_semver("01.0.0").is_err());
+    }
+
+    #[test]
+    fn tags() {
+        for valid in ["stable", "beta", "nightly", "lts-2024", "v2"] {
+            assert!(validate_tag(valid).is_ok(), "{valid} should be valid");
+        }
+
+        for invalid in ["", "1.0.0", "2", "x", "Beta", "-beta", "beta/1", "a b"] {
+            assert!(
+                validate_tag(invalid).is_err(),
+                "{invalid} should be invalid"
+            );
+        }
+    }
+}
@@ -0,0 +1,313 @@
+use std::{
+    cmp::Ordering,
+    convert::Infallible,
+    fmt::{self, Display},
+    hash::{Hash, Hasher},
+    str::FromStr,
+};
+
+/// A package version.
+///
+/// Versions that follow SemVer 2.0 (`MAJOR.MINOR.PATCH[-PRERELEASE][+BUILD]`)
+/// are ordered by semver precedence: release components are compared
+/// numerically, a pre-release version is lower than the release it precedes,
+/// and build metadata is ignored.
+///
+/// Any other string is parsed leniently with the same rules, so that versions
+/// such as `1.0` or `2024.10.3` are still ordered sensibly. Release components
+/// are compared one by one, and a version that runs out of components first is
+/// lower. Numeric components are lower than alphanumeric ones, which are
+/// compared lexically. Parsing never fails and numbers of any size are
+/// supported.
+///
+/// Versions with equal precedence but different text, such as `1.0.0+a` and
+/// `1.0.0+b`, are ordered by their text so that the ordering is total. Use
+/// [`Version::precedence`] to compare precedence alone.
+#[derive(Debug, Clone)]
+pub struct Version {
+    text: String,
+    release: Vec<Identifier>,
+    pre: Vec<Identifier>,
+    build: Option<String>,
+}
+
+/// A dot-separated component of a version.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+enum Identifier {
+    /// A numeric component, without leading zeros.
+    ///
+    /// Numbers are kept as text so that they can be of any size. Ordering by
+    /// length first orders them numerically.
+    Numeric { len: usize, digits: String },
+    /// A component that contains characters other than digits.
+    Alphanumeric(String),
+}
+
+impl Identifier {
+    /// Parses a version component.
+    fn parse(s: &str) -> Self {
+        if s.is_empty() || !s.bytes().all(|b| b.is_ascii_digit()) {
+            return Identifier::Alphanumeric(s.to_owned());
+        }
+
+        let digits = match s.trim_start_matches('0') {
+            "" => "0",
+            digits => digits,
+        };
+
+        Identifier::Numeric {
+            len: digits.len(),
+            digits: digits.to_owned(),
+        }
+    }
+
+    /// Checks whether the component is numeric without leading zeros, as
+    /// semver requires of numeric components.
+    fn is_canonical_number(&self, s: &str) -> bool {
+        matches!(self, Identifier::Numeric { digits, .. } if digits == s)
+    }
+}
+
+impl Version {
+    /// Parses a version.
+    ///
+    /// Parsing is lenient and never fails. Use [`Version::is_semver`] to check
+    /// whether the version is a valid semantic version.
+    pub fn parse(s: &str) -> Self {
+        let (rest, build) = match s.split_once('+') {
+            Some((rest, build)) => (rest, Some(build.to_owned())),
+            None => (s, None),
+        };
+
+        let (release, pre) = match rest.split_once('-') {
+            Some((release, pre)) => (release, Some(pre)),
+            None => (rest, None),
+        };
+
+        Self {
+            text: s.to_owned(),
+            release: release.split('.').map(Identifier::parse).collect(),
+            pre: pre
+                .map(|pre| pre.split('.').map(Identifier::parse).collect())
+                .unwrap_or_default(),
+            build,
+        }
+    }
+
+    /// Returns the version as written.
+    pub fn as_str(&self) -> &str {
+        &self.text
+    }
+
+    /// Checks whether the version is a pre-release version.
+    pub fn is_prerelease(&self) -> bool {
+        !self.pre.is_empty()
+    }
+
+    /// Checks whether the version is a valid SemVer 2.0 version.
+    pub fn is_semver(&self) -> bool {
+        let (rest, build) = match self.text.split_once('+') {
+            Some((rest, build)) => (rest, Some(build)),
+            None => (self.text.as_str(), None),
+        };
+
+        let (release, pre) = match rest.split_once('-') {
+            Some((release, pre)) => (release, Some(pre)),
+            None => (rest, None),
+        };
+
+        let release_valid = self.release.len() == 3
+            && release
+                .split('.')
+                .zip(&self.release)
+                .all(|(s, id)| id.is_canonical_number(s));
+
+        let pre_valid = pre.is_none_or(|pre| {
+            pre.split('.').zip(&self.pre).all(|(s, id)| {
+                is_identifier(s)
+                    && (matches!(id, Identifier::Alphanumeric(_)) || id.is_canonical_number(s))
+            })
+        });
+
+        let build_valid = build.is_none_or(|build| build.split('.').all(is_identifier));
+
+        release_valid && pre_valid && build_valid
+    }
+
+    /// Compares the precedence of two versions.
+    ///
+    /// Unlike [`Ord`], this ignores build metadata and other differences in
+    /// text, so `1.0.0+a` and `1.0.0+b` have equal precedence.
+    pub fn precedence(&self, other: &Self) -> Ordering {
+        self.release.cmp(&other.release).then_with(|| {
+            match (self.pre.is_empty(), other.pre.is_empty()) {
+                (true, true) => Ordering::Equal,
+                (true, false) => Ordering::Greater,
+                (false, true) => Ordering::Less,
+                (false, false) => self.pre.cmp(&other.pre),
+            }
+        })
+    }
+
+    /// Returns the version with missing release components filled in with
+    /// zeros, so that `1.0` has the same precedence as `1.0.0`.
+    pub(crate) fn padded(&self) -> Self {
+        let mut padded = self.clone();
+        while padded.release.len() < 3 {
+            padded.release.push(Identifier::parse("0"));
+        }
+        padded
+    }
+
+    /// Checks whether two versions have the same release components.
+    pub(crate) fn same_release(&self, other: &Self) -> bool {
+        self.release == other.release
+    }
+
+    /// Returns the build metadata of the version, if any.
+    pub fn build(&self) -> Option<&str> {
+        self.build.as_deref()
+    }
+}
+
+/// Checks whether a string is a valid semver identifier.
+fn is_identifier(s: &str) -> bool {
+    !s.is_empty() && s.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'-')
+}
+
+impl FromStr for Version {
+    type Err = Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Version::parse(s))
+    }
+}
+
+impl Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.text)
+    }
+}
+
+impl PartialEq for Version {
+    fn eq(&self, other: &Self) -> bool {
+        self.text == other.text
+    }
+}
+
+impl Eq for Version {}
+
+impl Hash for Version {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.text.hash(state);
+    }
+}
+
+impl PartialOrd for Version {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Version {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.precedence(other)
+            .then_with(|| self.text.cmp(&other.text))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cmp::Ordering;
+
+    use super::Version;
+
+    /// Asserts that versions are in strictly ascending order.
+    fn assert_ascending(versions: &[&str]) {
+        for pair in versions.windows(2) {
+            let (a, b) = (Version::parse(pair[0]), Version::parse(pair[1]));
+            assert_eq!(a.cmp(&b), Ordering::Less, "{a} should be less than {b}");
+            assert_eq!(
+                b.cmp(&a),
+                Ordering::Greater,
+                "{b} should be greater than {a}"
+            );
+        }
+    }
+
+    #[test]
+    fn semver_precedence() {
+        // the example from the semver 2.0 specification
+        assert_ascending(&[
+            "1.0.0-alpha",
+            "1.0.0-alpha.1",
+            "1.0.0-alpha.beta",
+            "1.0.0-beta",
+            "1.0.0-beta.2",
+            "1.0.0-beta.11",
+            "1.0.0-rc.1",
+            "1.0.0",
+            "2.0.0",
+            "2.1.0",
+            "2.1.1",
+        ]);
+    }
+
+    #[test]
+    fn build_metadata_is_ignored() {
+        let a = Version::parse("1.0.0+build.1");
+        let b = Version::parse("1.0.0+build.2");
+        assert_eq!(a.precedence(&b), Ordering::Equal);
+        assert_ne!(a, b);
+        assert_ne!(a.cmp(&b), Ordering::Equal);
+        assert_eq!(a.build(), Some("build.1"));
+    }
+
+    #[test]
+    fn large_components() {
+        assert_ascending(&[
+            "255.0.0",
+            "256.0.0",
+            "2024.9.30",
+            "2024.10.3",
+            "99999999999999999999999.0.0",
+        ]);
+    }
+
+    #[test]
+    fn lenient_versions() {
+        assert_ascending(&["1", "1.0", "1.0.0", "1.0.1", "1.1", "1.x", "v2"]);
+        assert_eq!(
+            Version::parse("01.2.3").precedence(&Version::parse("1.2.3")),
+            Ordering::Equal
+        );
+    }
+
+    #[test]
+    fn semver_validation() {
+        for valid in ["1.0.0", "1.0.0-alpha.1", "1.0.0-x-y.0+build.01", "0.0.0"] {
+            assert!(Version::parse(valid).is_semver(), "{valid} should be valid");
+        }
+
+        for invalid in [
+            "1.0",
+            "01.0.0",
+            "1.0.0-01",
+            "1.0.0-",
+            "1.0.0+",
+            "v1.0.0",
+            "1.0.0-a..b",
+        ] {
+            assert!(
+                !Version::parse(invalid).is_semver(),
+                "{invalid} should be invalid"
+            );
+        }
+    }
+
+    #[test]
+    fn prerelease() {
+        assert!(Version::parse("1.0.0-rc.1").is_prerelease());
+        assert!(!Version::parse("1.0.0+build").is_prerelease());
+    }
+}